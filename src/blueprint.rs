@@ -0,0 +1,136 @@
+use crate::config;
+use crate::rocket::Component;
+use crate::rocket::ComponentClass;
+use crate::rocket::Mount;
+use crate::rocket::Rocket;
+use crate::rocket::INITIAL_KNOWN_COMPONENTS;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+const EXTENSION: &str = "blueprint";
+
+/// Where the player's blueprints are kept, in the config directory.
+pub fn dir() -> Option<PathBuf> {
+    Some(config::dir()?.join("blueprints"))
+}
+
+/// A blueprint directory as it is shown to the player, with the home directory as `~` to keep
+/// it short.
+pub fn shown(dir: &Path) -> String {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match home.and_then(|home| dir.strip_prefix(home).ok().map(|rest| rest.to_path_buf())) {
+        Some(rest) => format!("~/{}", rest.display()),
+        None => dir.display().to_string(),
+    }
+}
+
+/// Writes out a design for people to read and share. The name comes first, then each component
+/// by its catalog name from the top of the rocket down, with a comment before each stage.
+pub fn to_text(rocket: &Rocket) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "name = {}\n", rocket.name);
+    let mut stage = rocket.stage_count();
+    let mut stage_starts = true;
+    for component in &rocket.components {
+        if stage_starts {
+            let _ = writeln!(text, "# Stage {}", stage);
+            stage_starts = false;
+        }
+        let _ = writeln!(text, "{}", component.name);
+        if let ComponentClass::Decoupler(Mount::Inline) = component.class {
+            stage -= 1;
+            stage_starts = true;
+        }
+    }
+    text
+}
+
+/// Reads a blueprint, taking its components from `known`. Errors say which line is at fault.
+pub fn parse(text: &str, known: &[Component]) -> Result<Rocket, String> {
+    let mut name = None;
+    let mut components = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let at_line = |e: String| format!("line {}: {}", idx + 1, e);
+        match config::key_value(line) {
+            Some(("name", _)) if name.is_some() => {
+                return Err(at_line("the name is given twice".into()))
+            }
+            Some(("name", value)) => name = Some(value.to_string()),
+            _ => components.push(component(line, known).map_err(at_line)?),
+        }
+    }
+    match name {
+        Some(name) if !name.is_empty() => Ok(Rocket { name, components }),
+        _ => Err("no name is given".to_string()),
+    }
+}
+
+fn component(name: &str, known: &[Component]) -> Result<Component, String> {
+    if let Some(component) = known.iter().find(|c| c.name == name) {
+        return Ok(component.clone());
+    }
+    //Until there is research, no component exists that is not known from the start
+    if INITIAL_KNOWN_COMPONENTS.iter().any(|c| c.name == name) {
+        Err(format!("{} has not been unlocked", name))
+    } else {
+        Err(format!("there is no component called {}", name))
+    }
+}
+
+/// Saves the design as a blueprint in `dir` named after it, replacing any from before, and
+/// returns where it went.
+pub fn export(dir: &Path, rocket: &Rocket) -> io::Result<PathBuf> {
+    let path = dir.join(format!("{}.{}", file_stem(&rocket.name), EXTENSION));
+    config::write(&path, &to_text(rocket))?;
+    Ok(path)
+}
+
+/// The design's name, made safe to use in a file name on any system.
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            c if c.is_whitespace() => Some('-'),
+            _ => None,
+        })
+        .collect();
+    if stem.is_empty() {
+        "rocket".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Reads every blueprint in `dir`, in order of file name, giving the file name with either the
+/// design or why it could not be read.
+pub fn import_all(
+    dir: &Path,
+    known: &[Component],
+) -> io::Result<Vec<(String, Result<Rocket, String>)>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(EXTENSION))
+        .collect();
+    paths.sort();
+    let read = paths.into_iter().map(|path| {
+        let file = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let rocket = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| parse(&text, known));
+        (file, rocket)
+    });
+    Ok(read.collect())
+}
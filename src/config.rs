@@ -0,0 +1,65 @@
+//! The files the game keeps in its config directory.
+//!
+//! They are plain text, one entry to a line, with blank lines and lines starting with `#`
+//! skipped. Their text is built up with `writeln!` into a `String`, ignoring the result, since
+//! writing to a `String` can not fail.
+
+use crate::debug_log::DEBUG;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The directory config files are kept in, following the XDG base directory spec.
+pub fn dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("cuneiforbits"))
+}
+
+/// Reads the config file `name`, or `None` if there is none or it can not be read.
+pub fn read(name: &str) -> Option<String> {
+    match fs::read_to_string(dir()?.join(name)) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            if e.kind() != io::ErrorKind::NotFound {
+                DEBUG.log(&format!("Could not read {}: {}", name, e));
+            }
+            None
+        }
+    }
+}
+
+/// The lines of a config file that hold an entry, trimmed.
+pub fn lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Splits a `key = value` line, or `None` if it has no `=`.
+pub fn key_value(line: &str) -> Option<(&str, &str)> {
+    let idx = line.find('=')?;
+    Some((line[..idx].trim(), line[idx + 1..].trim()))
+}
+
+/// Replaces the config file `name` with `text`.
+pub fn save(name: &str, text: &str) -> io::Result<()> {
+    let dir =
+        dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    write(&dir.join(name), text)
+}
+
+/// Replaces the file at `path` with `text`, creating its directory if need be. The text is
+/// written beside it first and moved into place, so the file is never left half written.
+pub fn write(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    fs::write(&partial, text)?;
+    fs::rename(&partial, path)
+}
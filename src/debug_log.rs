@@ -1,7 +1,6 @@
+use crate::ui_print;
 use crate::GAME;
 use std::fmt::Debug;
-use std::io::stdout;
-use std::io::Write;
 use std::sync::atomic::AtomicU16;
 use std::sync::atomic::Ordering;
 use std::sync::Mutex;
@@ -21,28 +20,33 @@ impl DebugLog {
     const LINE_LEN: usize = 60;
     const Y_OFFSET: u16 = 15;
 
+    /// Adds a message to the log, wrapping it over as many lines as it needs.
     pub fn log(&self, s: &str) {
-        if s.len() > DebugLog::LINE_LEN {
-            todo!();
+        let mut lines = self.lines.lock().unwrap();
+        let chars: Vec<char> = s.chars().collect();
+        if chars.is_empty() {
+            lines.push(String::new());
+        }
+        for line in chars.chunks(DebugLog::LINE_LEN) {
+            lines.push(line.iter().collect());
         }
-        self.lines.lock().unwrap().push(s.to_string());
     }
 
     pub fn redraw(&self) {
-        print!(
+        ui_print!(
             "{}{}",
             cursor::Goto(1, DebugLog::Y_OFFSET),
             clear::AfterCursor
         );
         for (idx, line) in self.lines.lock().unwrap().iter().enumerate() {
-            print!(
+            ui_print!(
                 "{}{}",
                 cursor::Goto(1, DebugLog::Y_OFFSET + idx as u16),
                 line
             );
         }
 
-        print!(
+        ui_print!(
             "{}",
             cursor::Goto(DebugLog::LINE_LEN as u16 + 1, DebugLog::Y_OFFSET)
         );
@@ -60,7 +64,7 @@ impl DebugLog {
                         break;
                     }
                     if row >= scroll {
-                        print!(
+                        ui_print!(
                             "{}",
                             cursor::Goto(
                                 DebugLog::LINE_LEN as u16 + 1,
@@ -71,13 +75,11 @@ impl DebugLog {
                 }
                 _ => {
                     if row >= scroll {
-                        print!("{}", c);
+                        ui_print!("{}", c);
                     }
                 }
             }
         }
-
-        stdout().flush().unwrap();
     }
 
     pub fn new() -> DebugLog {
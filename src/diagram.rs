@@ -0,0 +1,210 @@
+//! Draws rockets upright, as a stack of stages with any side boosters beside them.
+
+use crate::rocket::{Component, ComponentClass, CryoClass, Mount, Rocket, Size};
+
+/// The characters a diagram is drawn with. Every one is a single column wide.
+struct Palette {
+    side: char,
+    left: char,
+    right: char,
+    separator: char,
+    /// Tank fill, for room temperature, cryogenic and deep cryogenic propellant.
+    fills: [char; 3],
+}
+
+const UNICODE: Palette = Palette {
+    side: '│',
+    left: '╱',
+    right: '╲',
+    separator: '═',
+    fills: [' ', '░', '▒'],
+};
+
+const ASCII: Palette = Palette {
+    side: '|',
+    left: '/',
+    right: '\\',
+    separator: '=',
+    fills: [' ', '.', ':'],
+};
+
+/// The inside width of side boosters.
+const BOOSTER_WIDTH: usize = 2;
+
+/// A stage: the core, top to bottom, and the side boosters strapped to it, inside out.
+struct Stage<'a> {
+    core: Vec<&'a Component>,
+    boosters: Vec<Vec<&'a Component>>,
+}
+
+/// Draws `rocket` from the top down, one string per line, all padded to the same width.
+///
+/// Components are stacked in order. A stage decoupler ends a stage and the next component starts
+/// the one below it, and a booster decoupler puts the components after it, up to the next
+/// decoupler, in a pair of side boosters. Stages are wider the heavier they are and tanks taller.
+pub fn draw(rocket: &Rocket, ascii: bool) -> Vec<String> {
+    let palette = if ascii { &ASCII } else { &UNICODE };
+
+    let mut lines = Vec::new();
+    for stage in stages(rocket) {
+        let mass: f64 = stage.core.iter().map(|c| c.mass.in_kg()).sum();
+        let core = stack(&stage.core, 2 * scale(mass), palette);
+        let boosters: Vec<Vec<String>> = stage
+            .boosters
+            .iter()
+            .map(|booster| stack(booster, BOOSTER_WIDTH, palette))
+            .collect();
+
+        let height = boosters
+            .iter()
+            .map(Vec::len)
+            .chain(Some(core.len()))
+            .max()
+            .unwrap_or(0);
+        let core = bottom_align(core, height);
+        let boosters: Vec<Vec<String>> = boosters
+            .into_iter()
+            .map(|booster| bottom_align(booster, height))
+            .collect();
+        for row in 0..height {
+            let mut line = String::new();
+            for booster in boosters.iter().rev() {
+                line.push_str(&booster[row]);
+            }
+            line.push_str(&core[row]);
+            for booster in &boosters {
+                line.push_str(&booster[row]);
+            }
+            lines.push(line);
+        }
+    }
+
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    lines
+        .into_iter()
+        .map(|line| {
+            let pad = width - line.chars().count();
+            format!(
+                "{}{}{}",
+                " ".repeat(pad / 2),
+                line,
+                " ".repeat(pad - pad / 2)
+            )
+        })
+        .collect()
+}
+
+fn stages(rocket: &Rocket) -> Vec<Stage<'_>> {
+    let mut stages = vec![Stage {
+        core: Vec::new(),
+        boosters: Vec::new(),
+    }];
+    let mut in_booster = false;
+    for component in &rocket.components {
+        let stage = stages.last_mut().unwrap();
+        match component.class {
+            ComponentClass::Decoupler(Mount::Inline) => {
+                stage.core.push(component);
+                stages.push(Stage {
+                    core: Vec::new(),
+                    boosters: Vec::new(),
+                });
+                in_booster = false;
+            }
+            ComponentClass::Decoupler(Mount::Radial) => {
+                stage.boosters.push(Vec::new());
+                in_booster = true;
+            }
+            _ if in_booster => stage.boosters.last_mut().unwrap().push(component),
+            _ => stage.core.push(component),
+        }
+    }
+    stages.retain(|stage| !stage.core.is_empty() || !stage.boosters.is_empty());
+    stages
+}
+
+/// A rough size, from 1 to 4, for something of `kg`.
+fn scale(kg: f64) -> usize {
+    (1.0 + kg.max(1.0).log10()).min(4.0) as usize
+}
+
+/// Draws `components` on top of each other with an inside width of `inner`, which is even.
+fn stack(components: &[&Component], inner: usize, palette: &Palette) -> Vec<String> {
+    let mut lines = Vec::new();
+    for component in components {
+        let side = palette.side;
+        let body = |fill: char| format!("{}{}{}", side, fill.to_string().repeat(inner), side);
+        match component.class {
+            ComponentClass::Fairing(volume) => {
+                lines.extend(cone(inner, palette));
+                if Size::of(volume) == Size::Large {
+                    lines.push(body(' '));
+                }
+            }
+            ComponentClass::Capsule(crewed, _, _) => {
+                lines.extend(cone(inner, palette));
+                let window = if crewed { 'o' } else { '-' };
+                lines.push(format!("[{}]", centered(window, inner)));
+            }
+            ComponentClass::Tank(cryo, _, _) => {
+                let fill = match cryo {
+                    CryoClass::STP => palette.fills[0],
+                    CryoClass::Cryo => palette.fills[1],
+                    CryoClass::SuperCryo => palette.fills[2],
+                };
+                for _ in 0..scale(component.mass.in_kg()) {
+                    lines.push(body(fill));
+                }
+            }
+            ComponentClass::Engine(_, _) => {
+                lines.push(format!(
+                    "{}{}{}",
+                    palette.right,
+                    "_".repeat(inner),
+                    palette.left
+                ));
+                lines.push(format!(
+                    " {}{}{} ",
+                    palette.left,
+                    " ".repeat(inner - 2),
+                    palette.right
+                ));
+            }
+            ComponentClass::Decoupler(Mount::Inline) => {
+                lines.push(palette.separator.to_string().repeat(inner + 2));
+            }
+            ComponentClass::Decoupler(Mount::Radial) => {}
+        }
+    }
+    lines
+}
+
+/// A nose cone widening from the tip to an inside width of `inner`.
+fn cone(inner: usize, palette: &Palette) -> Vec<String> {
+    (0..=inner / 2)
+        .map(|row| {
+            let pad = " ".repeat(inner / 2 - row);
+            format!(
+                "{}{}{}{}{}",
+                pad,
+                palette.left,
+                " ".repeat(2 * row),
+                palette.right,
+                pad
+            )
+        })
+        .collect()
+}
+
+fn centered(c: char, inner: usize) -> String {
+    let pad = inner.saturating_sub(1);
+    format!("{}{}{}", " ".repeat(pad / 2), c, " ".repeat(pad - pad / 2))
+}
+
+/// Pads `lines` with blank lines above to `height`, so stages and boosters end level.
+fn bottom_align(lines: Vec<String>, height: usize) -> Vec<String> {
+    let width = lines.first().map_or(0, |l| l.chars().count());
+    let mut aligned = vec![" ".repeat(width); height - lines.len()];
+    aligned.extend(lines);
+    aligned
+}
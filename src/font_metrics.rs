@@ -0,0 +1,288 @@
+use crate::config;
+use crate::debug_log::DEBUG;
+use cuneiform_width::cuneiform_width;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::Mutex;
+
+lazy_static! {
+    pub static ref METRICS: Mutex<Box<dyn FontMetrics>> = Mutex::new(Box::new(DefaultMetrics));
+}
+
+/// How many terminal columns each character takes up in the font the terminal is using.
+pub trait FontMetrics: Send {
+    /// The number of columns `c` covers, which is zero for characters that only modify the one
+    /// before them.
+    fn width(&self, c: char) -> u16;
+
+    /// How many columns the terminal moves the cursor on by when `c` is printed. Where this is
+    /// less than the width, the rest is padded out with spaces.
+    fn advance(&self, c: char) -> u16 {
+        self.width(c)
+    }
+}
+
+/// The kinds of character the game draws, which terminals lay out alike.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GlyphClass {
+    Narrow,
+    /// Combining marks and other characters that only modify the one before them.
+    Zero,
+    Cuneiform,
+    /// East Asian wide and fullwidth characters.
+    Wide,
+    Emoji,
+    /// Geometric shapes, miscellaneous symbols and dingbats, like ▶ and ✔.
+    Symbol,
+}
+
+/// Widths for the Cuneiforbits font, falling back to what most terminals do for everything it
+/// does not cover.
+pub struct DefaultMetrics;
+
+/// The default metrics, corrected by how far the terminal was measured to advance the cursor for
+/// each class of glyph.
+pub struct Calibrated {
+    advances: HashMap<GlyphClass, u16>,
+}
+
+impl GlyphClass {
+    /// The classes that terminals disagree on, and so are worth measuring.
+    pub const MEASURED: [GlyphClass; 4] = [
+        GlyphClass::Cuneiform,
+        GlyphClass::Wide,
+        GlyphClass::Emoji,
+        GlyphClass::Symbol,
+    ];
+
+    pub fn of(c: char) -> GlyphClass {
+        match c as u32 {
+            0x12000..=0x1254F => GlyphClass::Cuneiform,
+            0x00..=0x1F | 0x7F..=0x9F => GlyphClass::Zero,
+            0x0300..=0x036F | 0x200B..=0x200F | 0x20D0..=0x20FF | 0xFE00..=0xFE0F => {
+                GlyphClass::Zero
+            }
+            0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x20000..=0x2FFFD
+            | 0x30000..=0x3FFFD => GlyphClass::Wide,
+            0x1F300..=0x1F64F | 0x1F680..=0x1F6FF | 0x1F900..=0x1F9FF => GlyphClass::Emoji,
+            //The Cuneiforbits font does not have these, so they are drawn from a fallback font
+            0x25A0..=0x27BF => GlyphClass::Symbol,
+            _ => GlyphClass::Narrow,
+        }
+    }
+
+    /// A character of the class to measure the terminal with.
+    pub fn sample(self) -> char {
+        match self {
+            GlyphClass::Narrow => 'a',
+            GlyphClass::Zero => '\u{301}',
+            GlyphClass::Cuneiform => '𒀀',
+            GlyphClass::Wide => '中',
+            GlyphClass::Emoji => '🚀',
+            GlyphClass::Symbol => '▶',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GlyphClass::Narrow => "narrow",
+            GlyphClass::Zero => "zero",
+            GlyphClass::Cuneiform => "cuneiform",
+            GlyphClass::Wide => "wide",
+            GlyphClass::Emoji => "emoji",
+            GlyphClass::Symbol => "symbol",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<GlyphClass> {
+        [
+            GlyphClass::Narrow,
+            GlyphClass::Zero,
+            GlyphClass::Cuneiform,
+            GlyphClass::Wide,
+            GlyphClass::Emoji,
+            GlyphClass::Symbol,
+        ]
+        .iter()
+        .copied()
+        .find(|class| class.name() == s)
+    }
+}
+
+impl FontMetrics for DefaultMetrics {
+    fn width(&self, c: char) -> u16 {
+        match GlyphClass::of(c) {
+            GlyphClass::Narrow => 1,
+            GlyphClass::Zero => 0,
+            GlyphClass::Cuneiform => cuneiform_width(c) as u16,
+            GlyphClass::Wide | GlyphClass::Emoji | GlyphClass::Symbol => 2,
+        }
+    }
+
+    fn advance(&self, c: char) -> u16 {
+        match GlyphClass::of(c) {
+            GlyphClass::Narrow | GlyphClass::Cuneiform | GlyphClass::Symbol => 1,
+            GlyphClass::Zero => 0,
+            GlyphClass::Wide | GlyphClass::Emoji => 2,
+        }
+    }
+}
+
+impl FontMetrics for Calibrated {
+    fn width(&self, c: char) -> u16 {
+        //A terminal that moves the cursor further than the glyph is wide leaves a gap, which is
+        //the glyph's to fill
+        DefaultMetrics.width(c).max(self.advance(c))
+    }
+
+    fn advance(&self, c: char) -> u16 {
+        match self.advances.get(&GlyphClass::of(c)) {
+            Some(&advance) => advance,
+            None => DefaultMetrics.advance(c),
+        }
+    }
+}
+
+impl Calibrated {
+    const FILE_NAME: &'static str = "glyph_widths";
+
+    /// Measures the terminal with `measure`, which gives how far the terminal advances the
+    /// cursor for a character, or `None` if it can not tell.
+    pub fn measure<F: FnMut(char) -> Option<u16>>(mut measure: F) -> Option<Calibrated> {
+        let mut advances = HashMap::new();
+        for &class in &GlyphClass::MEASURED {
+            advances.insert(class, measure(class.sample())?);
+        }
+        Some(Calibrated { advances })
+    }
+
+    /// Loads the measurements cached for the terminal `term`, if it has been calibrated.
+    pub fn load(term: &str) -> Option<Calibrated> {
+        Calibrated::parse(&config::read(Calibrated::FILE_NAME)?, term)
+    }
+
+    fn parse(text: &str, term: &str) -> Option<Calibrated> {
+        let mut advances = HashMap::new();
+        for line in config::lines(text) {
+            let mut words = line.split_whitespace();
+            let parsed = (|| {
+                let line_term = words.next()?;
+                let class = GlyphClass::parse(words.next()?)?;
+                let advance = words.next()?.parse().ok()?;
+                Some((line_term, class, advance))
+            })();
+            match parsed {
+                Some((line_term, class, advance)) if line_term == term => {
+                    advances.insert(class, advance);
+                }
+                Some(_) => {}
+                None => DEBUG.log(&format!("Bad glyph width line: {}", line)),
+            }
+        }
+
+        if GlyphClass::MEASURED
+            .iter()
+            .all(|class| advances.contains_key(class))
+        {
+            Some(Calibrated { advances })
+        } else {
+            None
+        }
+    }
+
+    /// Caches the measurements for the terminal `term`, keeping those for other terminals.
+    pub fn save(&self, term: &str) -> io::Result<()> {
+        let old = config::read(Calibrated::FILE_NAME).unwrap_or_default();
+        config::save(Calibrated::FILE_NAME, &self.to_text(&old, term))
+    }
+
+    /// The glyph widths file `old` with the measurements for `term` replaced by these.
+    fn to_text(&self, old: &str, term: &str) -> String {
+        let mut text = String::from("# <TERM> <glyph class> <cursor advance>\n");
+        for line in config::lines(old) {
+            if line.split_whitespace().next() != Some(term) {
+                let _ = writeln!(text, "{}", line);
+            }
+        }
+        for &class in &GlyphClass::MEASURED {
+            let _ = writeln!(text, "{} {} {}", term, class.name(), self.advances[&class]);
+        }
+        text
+    }
+}
+
+pub fn char_width(c: char) -> u16 {
+    METRICS.lock().unwrap().width(c)
+}
+
+pub fn char_advance(c: char) -> u16 {
+    METRICS.lock().unwrap().advance(c)
+}
+
+/// The number of columns `s` takes up on screen.
+pub fn width(s: &str) -> u16 {
+    let metrics = METRICS.lock().unwrap();
+    s.chars().map(|c| metrics.width(c)).sum()
+}
+
+/// The longest prefix of `s` that fits in `columns`.
+pub fn truncate(s: &str, columns: u16) -> &str {
+    let metrics = METRICS.lock().unwrap();
+    let mut used = 0;
+    for (idx, c) in s.char_indices() {
+        used += metrics.width(c);
+        if used > columns {
+            return &s[..idx];
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calibrated metrics where every measured class advances the cursor by `advance`.
+    fn all(advance: u16) -> Calibrated {
+        Calibrated::measure(|_| Some(advance)).unwrap()
+    }
+
+    #[test]
+    fn calibration_round_trips_per_terminal() {
+        let text = all(1).to_text("", "alacritty");
+        let text = all(2).to_text(&text, "xterm-256color");
+        //Calibrating again replaces the terminal's lines rather than adding to them
+        let text = all(3).to_text(&text, "alacritty");
+        assert_eq!(text.lines().count(), 1 + 2 * GlyphClass::MEASURED.len());
+
+        let alacritty = Calibrated::parse(&text, "alacritty").unwrap();
+        let xterm = Calibrated::parse(&text, "xterm-256color").unwrap();
+        for &class in &GlyphClass::MEASURED {
+            assert_eq!(alacritty.advance(class.sample()), 3);
+            assert_eq!(xterm.advance(class.sample()), 2);
+        }
+        assert!(Calibrated::parse(&text, "linux").is_none());
+    }
+
+    #[test]
+    fn partial_calibrations_are_not_used() {
+        let text = "xterm cuneiform 1\n\
+                    xterm wide two\n\
+                    xterm emoji 2\n\
+                    xterm symbol 1\n";
+        assert!(Calibrated::parse(text, "xterm").is_none());
+        assert!(Calibrated::measure(|c| if c == '🚀' { None } else { Some(1) }).is_none());
+    }
+}
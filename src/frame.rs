@@ -0,0 +1,584 @@
+use crate::font_metrics;
+use std::fmt::Write as _;
+use std::io::stdout;
+use std::io::Write as _;
+use std::mem;
+use std::sync::Mutex;
+
+lazy_static! {
+    pub static ref FRAME: Mutex<Frame> = Mutex::new(Frame::new(Box::new(Terminal)));
+}
+
+/// Where a frame is shown.
+pub trait Backend: Send {
+    /// The size of the screen, as `(width, height)`.
+    fn size(&self) -> (u16, u16);
+    fn write(&mut self, out: &str);
+}
+
+/// The real terminal, through stdout.
+pub struct Terminal;
+
+/// A screen of a fixed size that shows nothing, for drawing views in tests.
+#[cfg(test)]
+pub struct Headless {
+    pub width: u16,
+    pub height: u16,
+}
+
+/// An off-screen copy of the terminal that everything is drawn into.
+///
+/// Text and the escape sequences termion writes for cursor movement, clearing and styling are
+/// interpreted into a grid of cells. `present` then writes only the cells that differ from what
+/// is on the terminal, in a single write.
+pub struct Frame {
+    backend: Box<dyn Backend>,
+    back: Grid,
+    /// What the terminal is showing, as of the last `present`.
+    front: Grid,
+    cursor: (u16, u16),
+    saved_cursor: (u16, u16),
+    style: Style,
+    /// Where the terminal cursor is shown, or `None` if it is hidden.
+    shown_cursor: Option<(u16, u16)>,
+    /// An escape sequence that has been started but not finished.
+    escape: Option<String>,
+    /// Escape sequences the frame does not interpret, to be written out with the next `present`.
+    passthrough: String,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Grid {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cell {
+    Glyph {
+        c: char,
+        style: Style,
+    },
+    /// Covered by the wide glyph to the left.
+    Continuation,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub faint: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub invert: bool,
+    /// A 256 color palette index.
+    pub fg: Option<u8>,
+    pub bg: Option<u8>,
+}
+
+const BLANK: Cell = Cell::Glyph {
+    c: ' ',
+    style: Style {
+        bold: false,
+        faint: false,
+        italic: false,
+        underline: false,
+        invert: false,
+        fg: None,
+        bg: None,
+    },
+};
+
+impl Frame {
+    pub fn new(backend: Box<dyn Backend>) -> Frame {
+        let (width, height) = backend.size();
+        Frame {
+            backend,
+            back: Grid::new(width, height),
+            front: Grid::new(0, 0),
+            cursor: (1, 1),
+            saved_cursor: (1, 1),
+            style: Style::default(),
+            shown_cursor: None,
+            escape: None,
+            passthrough: String::new(),
+        }
+    }
+
+    pub fn write(&mut self, s: &str) {
+        for c in s.chars() {
+            if let Some(escape) = &mut self.escape {
+                escape.push(c);
+                if is_complete(escape) {
+                    let escape = self.escape.take().unwrap();
+                    self.escape_sequence(&escape);
+                }
+                continue;
+            }
+            match c {
+                '\x1B' => self.escape = Some(c.to_string()),
+                '\n' => self.cursor.1 += 1,
+                '\r' => self.cursor.0 = 1,
+                c => self.put(c),
+            }
+        }
+    }
+
+    /// Writes whatever has changed since the last call to the backend.
+    pub fn present(&mut self) {
+        let out = self.diff();
+        self.backend.write(&out);
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        self.backend.size()
+    }
+
+    /// The text on screen as of the last `present`, with trailing blanks trimmed.
+    #[cfg(test)]
+    pub fn text(&self) -> String {
+        let mut lines: Vec<String> = (1..=self.front.height)
+            .map(|y| {
+                let line: String = (1..=self.front.width)
+                    .filter_map(|x| match self.front.get(x, y) {
+                        Cell::Glyph { c, .. } => Some(c),
+                        Cell::Continuation => None,
+                    })
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect();
+        while matches!(lines.last(), Some(line) if line.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    /// Each run of inverted text on screen as of the last `present`, from the top left.
+    #[cfg(test)]
+    pub fn inverted(&self) -> Vec<String> {
+        let mut runs = Vec::new();
+        for y in 1..=self.front.height {
+            let mut run = String::new();
+            for x in 1..=self.front.width {
+                match self.front.get(x, y) {
+                    Cell::Glyph { c, style } if style.invert => run.push(c),
+                    Cell::Continuation => {}
+                    Cell::Glyph { .. } if !run.is_empty() => runs.push(mem::take(&mut run)),
+                    Cell::Glyph { .. } => {}
+                }
+            }
+            if !run.is_empty() {
+                runs.push(run);
+            }
+        }
+        runs
+    }
+
+    /// The escape sequences and text that bring the terminal from `front` to `back`.
+    fn diff(&mut self) -> String {
+        let mut out = mem::take(&mut self.passthrough);
+        //Writing to a String can not fail
+        if self.front.width != self.back.width || self.front.height != self.back.height {
+            let _ = write!(out, "{}{}", termion::style::Reset, termion::clear::All);
+            self.front = Grid::new(self.back.width, self.back.height);
+        }
+
+        let mut at = None;
+        let mut style = None;
+        for y in 1..=self.back.height {
+            for x in 1..=self.back.width {
+                let cell = self.back.get(x, y);
+                if cell == self.front.get(x, y) {
+                    continue;
+                }
+                let (c, cell_style) = match cell {
+                    Cell::Glyph { c, style } => (c, style),
+                    Cell::Continuation => continue,
+                };
+                if at != Some((x, y)) {
+                    let _ = write!(out, "{}", termion::cursor::Goto(x, y));
+                }
+                if style != Some(cell_style) {
+                    let _ = write!(out, "{}", cell_style.sgr());
+                    style = Some(cell_style);
+                }
+                let width = font_metrics::char_width(c);
+                out.push(c);
+                //Fill whatever the terminal leaves between the glyph and the next cell
+                let pad = width.saturating_sub(font_metrics::char_advance(c));
+                out.push_str(&" ".repeat(pad as usize));
+                at = Some((x + width, y));
+            }
+        }
+        if style.is_some() {
+            let _ = write!(out, "{}", termion::style::Reset);
+        }
+
+        match self.shown_cursor {
+            Some((x, y)) => {
+                let _ = write!(
+                    out,
+                    "{}{}",
+                    termion::cursor::Goto(x, y),
+                    termion::cursor::Show
+                );
+            }
+            None => {
+                let _ = write!(out, "{}", termion::cursor::Hide);
+            }
+        }
+
+        self.front = self.back.clone();
+        out
+    }
+
+    /// Draws `c` at the cursor and moves the cursor past it. Anything off the screen is dropped.
+    fn put(&mut self, c: char) {
+        let (x, y) = self.cursor;
+        let width = font_metrics::char_width(c);
+        //The frame has nowhere to keep characters that only modify the one before them
+        if width == 0 {
+            return;
+        }
+        self.cursor.0 += width;
+        if y > self.back.height || x + width - 1 > self.back.width {
+            return;
+        }
+
+        self.back.clear_glyph_at(x, y);
+        for dx in 1..width {
+            self.back.clear_glyph_at(x + dx, y);
+            self.back.set(x + dx, y, Cell::Continuation);
+        }
+        //A narrow glyph may have left the continuation of a wide one to its right orphaned
+        if let Some(Cell::Continuation) = self.back.try_get(x + width, y) {
+            self.back.set(x + width, y, BLANK);
+        }
+        self.back.set(
+            x,
+            y,
+            Cell::Glyph {
+                c,
+                style: self.style,
+            },
+        );
+    }
+
+    fn escape_sequence(&mut self, escape: &str) {
+        let body = match escape.strip_prefix("\x1B[") {
+            Some(body) => body,
+            None => {
+                self.passthrough.push_str(escape);
+                return;
+            }
+        };
+        let (params, command) = body.split_at(body.len() - 1);
+        let numbers: Vec<u16> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let number = |idx: usize, default: u16| match numbers.get(idx) {
+            Some(&n) if n != 0 => n,
+            _ => default,
+        };
+
+        match (params, command) {
+            ("?25", "l") => self.shown_cursor = None,
+            ("?25", "h") => self.shown_cursor = Some(self.cursor),
+            (_, "H") => self.cursor = (number(1, 1), number(0, 1)),
+            (_, "A") => self.cursor.1 = self.cursor.1.saturating_sub(number(0, 1)).max(1),
+            (_, "B") => self.cursor.1 += number(0, 1),
+            (_, "C") => self.cursor.0 += number(0, 1),
+            (_, "D") => self.cursor.0 = self.cursor.0.saturating_sub(number(0, 1)).max(1),
+            (_, "s") => self.saved_cursor = self.cursor,
+            (_, "u") => self.cursor = self.saved_cursor,
+            (_, "J") => self.clear(number(0, 0)),
+            (_, "K") => self.clear_line(number(0, 0)),
+            (_, "m") => self.style.apply_sgr(&numbers),
+            _ => self.passthrough.push_str(escape),
+        }
+    }
+
+    /// Clears after the cursor, before it, or everything, like the `J` escape sequence.
+    fn clear(&mut self, mode: u16) {
+        let y = self.cursor.1;
+        match mode {
+            0 => {
+                self.clear_line(0);
+                for row in y + 1..=self.back.height {
+                    self.back.clear_row(row, 1, self.back.width);
+                }
+            }
+            1 => {
+                for row in 1..y {
+                    self.back.clear_row(row, 1, self.back.width);
+                }
+                self.clear_line(1);
+            }
+            _ => {
+                //Everything is about to be redrawn, so this is when to catch up with a resize
+                let (width, height) = self.backend.size();
+                self.back = Grid::new(width, height);
+            }
+        }
+    }
+
+    /// Clears the line after the cursor, before it, or all of it, like the `K` escape sequence.
+    fn clear_line(&mut self, mode: u16) {
+        let (x, y) = self.cursor;
+        match mode {
+            0 => self.back.clear_row(y, x, self.back.width),
+            1 => self.back.clear_row(y, 1, x),
+            _ => self.back.clear_row(y, 1, self.back.width),
+        }
+    }
+}
+
+impl Grid {
+    fn new(width: u16, height: u16) -> Grid {
+        Grid {
+            width,
+            height,
+            cells: vec![BLANK; width as usize * height as usize],
+        }
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        if x == 0 || y == 0 || x > self.width || y > self.height {
+            None
+        } else {
+            Some((y - 1) as usize * self.width as usize + (x - 1) as usize)
+        }
+    }
+
+    fn try_get(&self, x: u16, y: u16) -> Option<Cell> {
+        self.index(x, y).map(|idx| self.cells[idx])
+    }
+
+    fn get(&self, x: u16, y: u16) -> Cell {
+        self.try_get(x, y).unwrap_or(BLANK)
+    }
+
+    fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        if let Some(idx) = self.index(x, y) {
+            self.cells[idx] = cell;
+        }
+    }
+
+    /// Blanks out the whole of whatever glyph covers the cell, so no half of a wide glyph is left.
+    fn clear_glyph_at(&mut self, x: u16, y: u16) {
+        let mut start = x;
+        while start > 1 && self.get(start, y) == Cell::Continuation {
+            start -= 1;
+        }
+        if let Cell::Glyph { c, .. } = self.get(start, y) {
+            for dx in 0..font_metrics::char_width(c) {
+                self.set(start + dx, y, BLANK);
+            }
+        }
+    }
+
+    /// Blanks the cells from `from` to `to` inclusive on row `y`.
+    fn clear_row(&mut self, y: u16, from: u16, to: u16) {
+        for x in from..=to.min(self.width) {
+            self.clear_glyph_at(x, y);
+        }
+    }
+}
+
+impl Style {
+    fn apply_sgr(&mut self, params: &[u16]) {
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.faint = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.invert = true,
+                21 => self.bold = false,
+                22 => {
+                    self.bold = false;
+                    self.faint = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.invert = false,
+                30..=37 => self.fg = Some((param - 30) as u8),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some((param - 40) as u8),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some((param - 90 + 8) as u8),
+                100..=107 => self.bg = Some((param - 100 + 8) as u8),
+                38 | 48 => {
+                    let color = match (params.next(), params.next()) {
+                        (Some(5), Some(n)) => Some(n as u8),
+                        _ => None,
+                    };
+                    if param == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The escape sequence that switches to exactly this style.
+    fn sgr(self) -> String {
+        let mut sgr = String::from("\x1B[0");
+        for (on, code) in &[
+            (self.bold, "1"),
+            (self.faint, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.invert, "7"),
+        ] {
+            if *on {
+                sgr.push(';');
+                sgr.push_str(code);
+            }
+        }
+        if let Some(fg) = self.fg {
+            let _ = write!(sgr, ";38;5;{}", fg);
+        }
+        if let Some(bg) = self.bg {
+            let _ = write!(sgr, ";48;5;{}", bg);
+        }
+        sgr.push('m');
+        sgr
+    }
+}
+
+impl Backend for Terminal {
+    fn size(&self) -> (u16, u16) {
+        termion::terminal_size().unwrap_or((80, 24))
+    }
+
+    fn write(&mut self, out: &str) {
+        let mut stdout = stdout();
+        stdout
+            .write_all(out.as_bytes())
+            .expect("IO error in printing");
+        stdout.flush().unwrap();
+    }
+}
+
+#[cfg(test)]
+impl Backend for Headless {
+    fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn write(&mut self, _: &str) {}
+}
+
+/// Whether `escape`, which starts with an escape character, is a whole escape sequence yet.
+fn is_complete(escape: &str) -> bool {
+    let mut chars = escape.chars().skip(1);
+    match chars.next() {
+        None => false,
+        Some('[') => match escape.chars().last() {
+            Some(last) if escape.len() > 2 => ('\x40'..='\x7E').contains(&last),
+            _ => false,
+        },
+        Some(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use termion::cursor::{self, Goto};
+
+    /// A small screen that keeps everything written to it.
+    struct Recorder(Arc<Mutex<String>>);
+
+    impl Backend for Recorder {
+        fn size(&self) -> (u16, u16) {
+            (8, 2)
+        }
+
+        fn write(&mut self, out: &str) {
+            self.0.lock().unwrap().push_str(out);
+        }
+    }
+
+    /// A frame drawn with `first` and presented, and what it has written so far.
+    fn presented(first: &str) -> (Frame, Arc<Mutex<String>>) {
+        let written = Arc::new(Mutex::new(String::new()));
+        let mut frame = Frame::new(Box::new(Recorder(Arc::clone(&written))));
+        frame.write(first);
+        frame.present();
+        (frame, written)
+    }
+
+    /// Draws `s`, presents it and returns what was written to the terminal for it.
+    fn update(frame: &mut Frame, written: &Mutex<String>, s: &str) -> String {
+        written.lock().unwrap().clear();
+        frame.write(s);
+        frame.present();
+        mem::take(&mut *written.lock().unwrap())
+    }
+
+    #[test]
+    fn unchanged_cells_are_not_written() {
+        let (mut frame, written) = presented(&format!("{}abc", Goto(1, 1)));
+        //The first present has nothing on screen to go by, so it clears it and draws everything
+        let first = mem::take(&mut *written.lock().unwrap());
+        assert!(first.starts_with(&format!("{}{}", termion::style::Reset, termion::clear::All)));
+        assert!(first.contains("abc"));
+
+        let out = update(&mut frame, &written, &format!("{}abc", Goto(1, 1)));
+        assert_eq!(out, cursor::Hide.to_string());
+
+        let out = update(&mut frame, &written, &format!("{}X", Goto(2, 1)));
+        let expected = format!(
+            "{}{}X{}{}",
+            Goto(2, 1),
+            Style::default().sgr(),
+            termion::style::Reset,
+            cursor::Hide
+        );
+        assert_eq!(out, expected);
+        assert_eq!(frame.text(), "aXc");
+    }
+
+    #[test]
+    fn style_changes_are_written() {
+        let (mut frame, written) = presented(&format!("{}ab", Goto(1, 2)));
+        let invert = termion::style::Invert;
+        let out = update(&mut frame, &written, &format!("{}{}b", Goto(2, 2), invert));
+        let inverted = Style {
+            invert: true,
+            ..Style::default()
+        };
+        assert_eq!(
+            out,
+            format!(
+                "{}{}b{}{}",
+                Goto(2, 2),
+                inverted.sgr(),
+                termion::style::Reset,
+                cursor::Hide
+            )
+        );
+        assert_eq!(frame.inverted(), ["b"]);
+    }
+
+    #[test]
+    fn overwriting_half_a_wide_glyph_clears_the_rest() {
+        let (mut frame, written) = presented(&format!("{}中▶x", Goto(1, 1)));
+        //The symbol is two columns wide but moves the cursor one, so it is padded
+        assert!(written.lock().unwrap().contains("中▶ x"));
+
+        update(
+            &mut frame,
+            &written,
+            &format!("{}y{}z", Goto(2, 1), Goto(3, 1)),
+        );
+        assert_eq!(frame.text(), " yz x");
+    }
+}
@@ -0,0 +1,64 @@
+use crate::settings::GlyphMode;
+use crate::settings::SETTINGS;
+
+/// A decorative glyph, with stand-ins for terminals that can not show it.
+#[derive(Clone, Copy, Debug)]
+pub struct Glyph {
+    pub cuneiform: &'static str,
+    pub unicode: &'static str,
+    pub ascii: &'static str,
+}
+
+/// Marks the selected item of a list.
+pub const SELECTED: Glyph = Glyph {
+    cuneiform: "▶",
+    unicode: "▶",
+    ascii: ">",
+};
+
+pub const ACCEPT: Glyph = Glyph {
+    cuneiform: "✔",
+    unicode: "✔",
+    ascii: "v",
+};
+
+pub const SCROLL_TRACK: Glyph = Glyph {
+    cuneiform: "│",
+    unicode: "│",
+    ascii: "|",
+};
+
+pub const SCROLL_THUMB: Glyph = Glyph {
+    cuneiform: "█",
+    unicode: "█",
+    ascii: "#",
+};
+
+/// Separates the titles in the breadcrumbs.
+pub const CRUMB_SEPARATOR: Glyph = Glyph {
+    cuneiform: "›",
+    unicode: "›",
+    ascii: ">",
+};
+
+/// Decorates placeholders for parts of the game that do not exist yet.
+pub const UNFINISHED: Glyph = Glyph {
+    cuneiform: "𒀿",
+    unicode: "※",
+    ascii: "*",
+};
+
+impl Glyph {
+    /// The glyph for the glyph mode in use.
+    pub fn get(self) -> &'static str {
+        self.pick(SETTINGS.lock().unwrap().glyphs())
+    }
+
+    pub fn pick(self, mode: GlyphMode) -> &'static str {
+        match mode {
+            GlyphMode::Cuneiform => self.cuneiform,
+            GlyphMode::Auto | GlyphMode::Unicode => self.unicode,
+            GlyphMode::Ascii => self.ascii,
+        }
+    }
+}
@@ -1,11 +1,10 @@
 #![allow(dead_code)]
 
-
-
-
+use crate::numerals::Number;
 use crate::orbit::Orbit;
 use crate::sats::Sat;
 use crate::sats::{CubeSat, CubeSatClass, LargeSat, SatArray, SatId};
+use crate::settings::SETTINGS;
 use crate::units::*;
 use crate::GAME;
 use rand::{seq::SliceRandom, thread_rng, Rng};
@@ -58,16 +57,18 @@ impl Job {
     }
 }
 
-const TARGET_CUSTOMERS: u8 = 5;
-
 impl CustomerRegistry {
     pub fn new() -> CustomerRegistry {
         CustomerRegistry {
             customers: Mutex::new(Vec::new()),
-            target_customers: AtomicU8::new(TARGET_CUSTOMERS),
+            target_customers: AtomicU8::new(SETTINGS.lock().unwrap().difficulty.target_customers()),
         }
     }
 
+    pub fn set_target_customers(&self, target: u8) {
+        self.target_customers.store(target, Ordering::Relaxed);
+    }
+
     fn get_or_generate(&self) -> CustomerId {
         let idx = thread_rng().gen_range(0, self.target_customers.load(Ordering::Relaxed));
         let mut customers = self.customers.lock().unwrap();
@@ -89,6 +90,24 @@ impl CustomerRegistry {
     }
 }
 
+#[cfg(test)]
+impl CustomerRegistry {
+    /// Forgets every customer, as in a new game.
+    pub fn reset(&self) {
+        self.customers.lock().unwrap().clear();
+        self.set_target_customers(SETTINGS.lock().unwrap().difficulty.target_customers());
+    }
+
+    /// Adds a customer with a known name, where a generated one would be random.
+    pub fn add(&self, name: &str) -> CustomerId {
+        let mut customers = self.customers.lock().unwrap();
+        customers.push(Customer {
+            name: name.to_string(),
+        });
+        CustomerId((customers.len() - 1) as u32)
+    }
+}
+
 impl Customer {
     fn generate() -> Customer {
         Customer {
@@ -126,38 +145,30 @@ impl Payload {
 impl Display for Payload {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::CubeSat(sat) => write!(
-                f,
-                "{} CubeSat of {} kg to {}",
-                sat.class,
-                sat.mass.in_kg(),
-                sat.orbit
-            ),
-            Self::LargeSat(sat) => write!(
-                f,
-                "{} kg {} m³ Satalite to {}",
-                sat.mass.in_kg(),
-                sat.volume.in_m3(),
-                sat.orbit
-            ),
+            Self::CubeSat(sat) => {
+                write!(f, "{} CubeSat of {} to {}", sat.class, sat.mass, sat.orbit)
+            }
+            Self::LargeSat(sat) => {
+                write!(f, "{} {} Satalite to {}", sat.mass, sat.volume, sat.orbit)
+            }
             Self::SatArray(sats) => write!(
                 f,
-                "Array of {} Satalites of total {} kg and {} m³",
-                sats.orbits.len(),
-                sats.base_mass.in_kg() + sats.sat_mass.in_kg() * sats.orbits.len() as f64,
-                sats.volume.in_m3()
+                "Array of {} Satalites of total {} and {}",
+                Number::new(sats.orbits.len() as f64),
+                sats.base_mass + sats.sat_mass * sats.orbits.len() as u64,
+                sats.volume
             ),
             Self::Station(sat_id, cargo) => write!(
                 f,
-                "Delivery to {} in {} of {} kg, {} m³",
+                "Delivery to {} in {} of {}, {}",
                 if let Sat::Station(sta) = GAME.sats.get(*sat_id).unwrap() {
                     &sta.name
                 } else {
                     "a satalite"
                 },
                 GAME.sats.get(*sat_id).unwrap().orbit(),
-                cargo.mass.in_kg(),
-                cargo.volume.in_m3()
+                cargo.mass,
+                cargo.volume
             ),
         }
         .unwrap();
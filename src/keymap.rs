@@ -0,0 +1,608 @@
+use crate::config;
+use crate::debug_log::DEBUG;
+use crate::settings::Choice;
+use crate::ui::Input;
+use crate::ui::InputMode;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::Mutex;
+use termion::event::Key;
+
+lazy_static! {
+    pub static ref KEYMAP: Mutex<Keymap> = Mutex::new(Keymap::preset(Preset::Default));
+}
+
+/// Which `Input` each key produces, for each `InputMode` that can be rebound.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    control: HashMap<Key, Input>,
+    typing: HashMap<Key, Input>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    Default,
+    Vi,
+    Emacs,
+}
+
+/// The modes with a rebindable table, in the order they are listed.
+pub const MODES: [InputMode; 2] = [InputMode::Control, InputMode::Type];
+
+/// The inputs that can be bound to keys, in the order they are listed.
+pub const BINDABLE: [Input; 31] = [
+    Input::Up,
+    Input::Down,
+    Input::Left,
+    Input::Right,
+    Input::Select,
+    Input::Back,
+    Input::Del,
+    Input::BkSpace,
+    Input::WordLeft,
+    Input::WordRight,
+    Input::WordBkSpace,
+    Input::WordDel,
+    Input::Mark,
+    Input::Cut,
+    Input::Copy,
+    Input::Paste,
+    Input::Duplicate,
+    Input::MoveLeft,
+    Input::MoveRight,
+    Input::InsertBefore,
+    Input::InsertAfter,
+    Input::Undo,
+    Input::Redo,
+    Input::PageUp,
+    Input::PageDown,
+    Input::Home,
+    Input::End,
+    Input::Search,
+    Input::Details,
+    Input::Help,
+    Input::Palette,
+];
+
+impl Choice for Preset {
+    const ALL: &'static [Preset] = &[Preset::Default, Preset::Vi, Preset::Emacs];
+
+    fn name(self) -> &'static str {
+        match self {
+            Preset::Default => "Default",
+            Preset::Vi => "vi",
+            Preset::Emacs => "Emacs",
+        }
+    }
+}
+
+impl Keymap {
+    const FILE_NAME: &'static str = "keymap";
+
+    pub fn preset(preset: Preset) -> Keymap {
+        use Key::*;
+
+        type Bindings = &'static [(Key, Input)];
+
+        let (control, typing): (Bindings, Bindings) = match preset {
+            Preset::Default => (
+                &[
+                    (Left, Input::Left),
+                    (Char('a'), Input::Left),
+                    (Right, Input::Right),
+                    (Char('d'), Input::Right),
+                    (Up, Input::Up),
+                    (Char('w'), Input::Up),
+                    (Down, Input::Down),
+                    (Char('s'), Input::Down),
+                    (Char('\n'), Input::Select),
+                    (Char(' '), Input::Select),
+                    (Esc, Input::Back),
+                    (Delete, Input::Del),
+                    (Backspace, Input::Del),
+                    (PageUp, Input::PageUp),
+                    (PageDown, Input::PageDown),
+                    (Home, Input::Home),
+                    (End, Input::End),
+                    (Char('/'), Input::Search),
+                    (Char('\t'), Input::Details),
+                    (Char('?'), Input::Help),
+                    (F(1), Input::Help),
+                    (Char(':'), Input::Palette),
+                    (Ctrl('k'), Input::Palette),
+                    (Char('m'), Input::Mark),
+                    (Char('x'), Input::Cut),
+                    (Char('c'), Input::Copy),
+                    (Char('v'), Input::Paste),
+                    (Ctrl('d'), Input::Duplicate),
+                    (Char('<'), Input::MoveLeft),
+                    (Char(','), Input::MoveLeft),
+                    (Char('>'), Input::MoveRight),
+                    (Char('.'), Input::MoveRight),
+                    (Insert, Input::InsertBefore),
+                    (Char('i'), Input::InsertBefore),
+                    (Char('o'), Input::InsertAfter),
+                    (Ctrl('z'), Input::Undo),
+                    (Char('z'), Input::Undo),
+                    (Ctrl('y'), Input::Redo),
+                    (Char('Z'), Input::Redo),
+                ],
+                &[
+                    (Left, Input::Left),
+                    (Ctrl('b'), Input::Left),
+                    (Right, Input::Right),
+                    (Ctrl('f'), Input::Right),
+                    (Up, Input::Up),
+                    (Ctrl('p'), Input::Up),
+                    (Down, Input::Down),
+                    (Ctrl('n'), Input::Down),
+                    (Delete, Input::Del),
+                    (Ctrl('d'), Input::Del),
+                    (Backspace, Input::BkSpace),
+                    (Alt('b'), Input::WordLeft),
+                    (Alt('f'), Input::WordRight),
+                    (Ctrl('w'), Input::WordBkSpace),
+                    (Alt('d'), Input::WordDel),
+                    (Home, Input::Home),
+                    (Ctrl('a'), Input::Home),
+                    (End, Input::End),
+                    (Ctrl('e'), Input::End),
+                    (Ctrl('s'), Input::Select),
+                    (Esc, Input::Back),
+                    (F(1), Input::Help),
+                    (Ctrl('k'), Input::Palette),
+                ],
+            ),
+            Preset::Vi => (
+                &[
+                    (Left, Input::Left),
+                    (Char('h'), Input::Left),
+                    (Right, Input::Right),
+                    (Char('l'), Input::Right),
+                    (Up, Input::Up),
+                    (Char('k'), Input::Up),
+                    (Down, Input::Down),
+                    (Char('j'), Input::Down),
+                    (Char('\n'), Input::Select),
+                    (Char(' '), Input::Select),
+                    (Esc, Input::Back),
+                    (Char('q'), Input::Back),
+                    (Char('x'), Input::Del),
+                    (Delete, Input::Del),
+                    (PageUp, Input::PageUp),
+                    (Ctrl('u'), Input::PageUp),
+                    (PageDown, Input::PageDown),
+                    (Ctrl('d'), Input::PageDown),
+                    (Home, Input::Home),
+                    (Char('g'), Input::Home),
+                    (End, Input::End),
+                    (Char('G'), Input::End),
+                    (Char('/'), Input::Search),
+                    (Char('\t'), Input::Details),
+                    (Char('?'), Input::Help),
+                    (F(1), Input::Help),
+                    (Char(':'), Input::Palette),
+                    (Char('v'), Input::Mark),
+                    (Char('d'), Input::Cut),
+                    (Char('y'), Input::Copy),
+                    (Char('p'), Input::Paste),
+                    (Char('+'), Input::Duplicate),
+                    (Char('<'), Input::MoveLeft),
+                    (Char('>'), Input::MoveRight),
+                    (Char('i'), Input::InsertBefore),
+                    (Char('a'), Input::InsertAfter),
+                    (Char('u'), Input::Undo),
+                    (Ctrl('r'), Input::Redo),
+                ],
+                &[
+                    (Left, Input::Left),
+                    (Right, Input::Right),
+                    (Up, Input::Up),
+                    (Down, Input::Down),
+                    (Delete, Input::Del),
+                    (Backspace, Input::BkSpace),
+                    (Ctrl('h'), Input::BkSpace),
+                    (Ctrl('w'), Input::WordBkSpace),
+                    (Home, Input::Home),
+                    (End, Input::End),
+                    (Ctrl('s'), Input::Select),
+                    (Esc, Input::Back),
+                    (F(1), Input::Help),
+                ],
+            ),
+            Preset::Emacs => (
+                &[
+                    (Left, Input::Left),
+                    (Ctrl('b'), Input::Left),
+                    (Right, Input::Right),
+                    (Ctrl('f'), Input::Right),
+                    (Up, Input::Up),
+                    (Ctrl('p'), Input::Up),
+                    (Down, Input::Down),
+                    (Ctrl('n'), Input::Down),
+                    (Char('\n'), Input::Select),
+                    (Esc, Input::Back),
+                    (Ctrl('g'), Input::Back),
+                    (Ctrl('d'), Input::Del),
+                    (Delete, Input::Del),
+                    (PageUp, Input::PageUp),
+                    (Alt('v'), Input::PageUp),
+                    (PageDown, Input::PageDown),
+                    (Ctrl('v'), Input::PageDown),
+                    (Home, Input::Home),
+                    (Alt('<'), Input::Home),
+                    (End, Input::End),
+                    (Alt('>'), Input::End),
+                    (Ctrl('s'), Input::Search),
+                    (Char('\t'), Input::Details),
+                    (Ctrl('h'), Input::Help),
+                    (F(1), Input::Help),
+                    (Alt('x'), Input::Palette),
+                    (Null, Input::Mark),
+                    (Ctrl('w'), Input::Cut),
+                    (Alt('w'), Input::Copy),
+                    (Ctrl('y'), Input::Paste),
+                    (Alt('d'), Input::Duplicate),
+                    (Alt('b'), Input::MoveLeft),
+                    (Alt('f'), Input::MoveRight),
+                    (Ctrl('o'), Input::InsertBefore),
+                    (Alt('o'), Input::InsertAfter),
+                    //Terminals send C-/ and C-_ as the same byte, which termion reads as C-7
+                    (Ctrl('7'), Input::Undo),
+                    (Alt('_'), Input::Redo),
+                ],
+                &[
+                    (Left, Input::Left),
+                    (Ctrl('b'), Input::Left),
+                    (Right, Input::Right),
+                    (Ctrl('f'), Input::Right),
+                    (Up, Input::Up),
+                    (Ctrl('p'), Input::Up),
+                    (Down, Input::Down),
+                    (Ctrl('n'), Input::Down),
+                    (Delete, Input::Del),
+                    (Ctrl('d'), Input::Del),
+                    (Backspace, Input::BkSpace),
+                    (Alt('b'), Input::WordLeft),
+                    (Alt('f'), Input::WordRight),
+                    (Ctrl('w'), Input::WordBkSpace),
+                    (Alt('d'), Input::WordDel),
+                    (Home, Input::Home),
+                    (Ctrl('a'), Input::Home),
+                    (End, Input::End),
+                    (Ctrl('e'), Input::End),
+                    (Ctrl('s'), Input::Select),
+                    (Esc, Input::Back),
+                    (Ctrl('g'), Input::Back),
+                    (Ctrl('h'), Input::Help),
+                    (F(1), Input::Help),
+                    (Alt('x'), Input::Palette),
+                ],
+            ),
+        };
+
+        Keymap {
+            control: control.iter().copied().collect(),
+            typing: typing.iter().copied().collect(),
+        }
+    }
+
+    fn table(&self, mode: InputMode) -> Option<&HashMap<Key, Input>> {
+        match mode {
+            InputMode::Control => Some(&self.control),
+            InputMode::Type => Some(&self.typing),
+            InputMode::Capture => None,
+        }
+    }
+
+    fn table_mut(&mut self, mode: InputMode) -> Option<&mut HashMap<Key, Input>> {
+        match mode {
+            InputMode::Control => Some(&mut self.control),
+            InputMode::Type => Some(&mut self.typing),
+            InputMode::Capture => None,
+        }
+    }
+
+    pub fn get(&self, mode: InputMode, key: Key) -> Option<Input> {
+        self.table(mode)?.get(&key).copied()
+    }
+
+    /// All keys bound to `input` in `mode`, sorted by name.
+    pub fn keys_for(&self, mode: InputMode, input: Input) -> Vec<Key> {
+        let mut keys: Vec<Key> = match self.table(mode) {
+            Some(table) => table
+                .iter()
+                .filter(|&(_, &i)| i == input)
+                .map(|(&k, _)| k)
+                .collect(),
+            None => Vec::new(),
+        };
+        keys.sort_by_key(|&k| key_name(k));
+        keys
+    }
+
+    /// Binds `key` to `input` in `mode`, returning the input it was previously bound to.
+    pub fn bind(&mut self, mode: InputMode, key: Key, input: Input) -> Option<Input> {
+        self.table_mut(mode)?.insert(key, input)
+    }
+
+    pub fn unbind(&mut self, mode: InputMode, key: Key) {
+        if let Some(table) = self.table_mut(mode) {
+            table.remove(&key);
+        }
+    }
+
+    /// Loads the keymap from the config file, falling back to the default preset if there is
+    /// none. Lines that can not be understood are skipped. Inputs the file does not list, such
+    /// as ones added since it was saved, get their default keys where those are still free.
+    pub fn load() -> Keymap {
+        match config::read(Keymap::FILE_NAME) {
+            Some(text) => Keymap::parse(&text),
+            None => Keymap::preset(Preset::Default),
+        }
+    }
+
+    fn parse(text: &str) -> Keymap {
+        let mut keymap = Keymap {
+            control: HashMap::new(),
+            typing: HashMap::new(),
+        };
+        let mut listed = Vec::new();
+        for line in config::lines(text) {
+            let mut words = line.split_whitespace();
+            let parsed = (|| {
+                let mode = parse_mode(words.next()?)?;
+                let input = parse_input(words.next()?)?;
+                let key = match words.next()? {
+                    "none" => None,
+                    key => Some(parse_key(key)?),
+                };
+                Some((mode, input, key))
+            })();
+            match parsed {
+                Some((mode, input, key)) => {
+                    listed.push((mode, input));
+                    if let Some(key) = key {
+                        keymap.bind(mode, key, input);
+                    }
+                }
+                None => DEBUG.log(&format!("Bad keymap line: {}", line)),
+            }
+        }
+
+        let default = Keymap::preset(Preset::Default);
+        for &mode in &MODES {
+            for &input in &BINDABLE {
+                let must_stay_bound =
+                    mode == InputMode::Control && (input == Input::Back || input == Input::Select);
+                for key in default.keys_for(mode, input) {
+                    if (must_stay_bound && keymap.keys_for(mode, input).is_empty())
+                        || (!listed.contains(&(mode, input)) && keymap.get(mode, key).is_none())
+                    {
+                        keymap.bind(mode, key, input);
+                    }
+                }
+            }
+        }
+        keymap
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        config::save(Keymap::FILE_NAME, &self.to_text())
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::from("# <mode> <input> <key, or none>\n");
+        for &mode in &MODES {
+            for &input in &BINDABLE {
+                let keys = self.keys_for(mode, input);
+                if keys.is_empty() {
+                    let _ = writeln!(text, "{} {} none", mode_name(mode), input_name(input));
+                }
+                for key in keys {
+                    let _ = writeln!(
+                        text,
+                        "{} {} {}",
+                        mode_name(mode),
+                        input_name(input),
+                        key_name(key)
+                    );
+                }
+            }
+        }
+        text
+    }
+}
+
+pub fn mode_name(mode: InputMode) -> &'static str {
+    match mode {
+        InputMode::Control => "control",
+        InputMode::Type => "type",
+        InputMode::Capture => "capture",
+    }
+}
+
+fn parse_mode(s: &str) -> Option<InputMode> {
+    MODES.iter().copied().find(|&m| mode_name(m) == s)
+}
+
+pub fn input_name(input: Input) -> &'static str {
+    match input {
+        Input::Up => "Up",
+        Input::Down => "Down",
+        Input::Left => "Left",
+        Input::Right => "Right",
+        Input::Select => "Select",
+        Input::Back => "Back",
+        Input::Del => "Delete",
+        Input::BkSpace => "Backspace",
+        Input::WordLeft => "WordLeft",
+        Input::WordRight => "WordRight",
+        Input::WordBkSpace => "WordBackspace",
+        Input::WordDel => "WordDelete",
+        Input::Mark => "Mark",
+        Input::Cut => "Cut",
+        Input::Copy => "Copy",
+        Input::Paste => "Paste",
+        Input::Duplicate => "Duplicate",
+        Input::MoveLeft => "MoveLeft",
+        Input::MoveRight => "MoveRight",
+        Input::InsertBefore => "InsertBefore",
+        Input::InsertAfter => "InsertAfter",
+        Input::Undo => "Undo",
+        Input::Redo => "Redo",
+        Input::PageUp => "PageUp",
+        Input::PageDown => "PageDown",
+        Input::Home => "Home",
+        Input::End => "End",
+        Input::Search => "Search",
+        Input::Details => "Details",
+        Input::Help => "Help",
+        Input::Palette => "Palette",
+        Input::Type(_) | Input::Key(_) => "",
+    }
+}
+
+fn parse_input(s: &str) -> Option<Input> {
+    BINDABLE.iter().copied().find(|&i| input_name(i) == s)
+}
+
+/// A short, human readable name for a key, which `parse_key` turns back into the key.
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char('\n') => "Enter".to_string(),
+        Key::Char(' ') => "Space".to_string(),
+        Key::Char('\t') => "Tab".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(c) => format!("C-{}", c),
+        Key::Alt(c) => format!("M-{}", c),
+        Key::F(n) => format!("F{}", n),
+        Key::Backspace => "Backspace".to_string(),
+        Key::Left => "Left".to_string(),
+        Key::Right => "Right".to_string(),
+        Key::Up => "Up".to_string(),
+        Key::Down => "Down".to_string(),
+        Key::Home => "Home".to_string(),
+        Key::End => "End".to_string(),
+        Key::PageUp => "PageUp".to_string(),
+        Key::PageDown => "PageDown".to_string(),
+        Key::BackTab => "BackTab".to_string(),
+        Key::Delete => "Delete".to_string(),
+        Key::Insert => "Insert".to_string(),
+        Key::Esc => "Esc".to_string(),
+        //What terminals send for C-Space
+        Key::Null => "C-Space".to_string(),
+        Key::__IsNotComplete => "?".to_string(),
+    }
+}
+
+fn parse_key(s: &str) -> Option<Key> {
+    fn single(s: &str) -> Option<char> {
+        let mut chars = s.chars();
+        let c = chars.next()?;
+        if chars.next().is_none() {
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    let key = match s {
+        "Enter" => Key::Char('\n'),
+        "Space" => Key::Char(' '),
+        "Tab" => Key::Char('\t'),
+        "Backspace" => Key::Backspace,
+        "Left" => Key::Left,
+        "Right" => Key::Right,
+        "Up" => Key::Up,
+        "Down" => Key::Down,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        "BackTab" => Key::BackTab,
+        "Delete" => Key::Delete,
+        "Insert" => Key::Insert,
+        "Esc" => Key::Esc,
+        "C-Space" => Key::Null,
+        _ => {
+            if let Some(c) = single(s) {
+                Key::Char(c)
+            } else if let Some(c) = s.strip_prefix("C-") {
+                Key::Ctrl(single(c)?)
+            } else if let Some(c) = s.strip_prefix("M-") {
+                Key::Alt(single(c)?)
+            } else if let Some(n) = s.strip_prefix('F') {
+                Key::F(n.parse().ok()?)
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_round_trip() {
+        for &preset in &[Preset::Default, Preset::Vi, Preset::Emacs] {
+            let text = Keymap::preset(preset).to_text();
+            assert_eq!(Keymap::parse(&text).to_text(), text, "{:?}", preset);
+        }
+    }
+
+    #[test]
+    fn key_names_parse_back() {
+        let keys = [
+            Key::Char('\n'),
+            Key::Char(' '),
+            Key::Char('\t'),
+            Key::Char('q'),
+            Key::Char('#'),
+            Key::Ctrl('s'),
+            Key::Alt('x'),
+            Key::F(12),
+            Key::BackTab,
+            Key::Esc,
+            Key::Null,
+        ];
+        for &key in &keys {
+            assert_eq!(parse_key(&key_name(key)), Some(key));
+        }
+    }
+
+    #[test]
+    fn keymap_files_fill_in_what_they_leave_out() {
+        let text = "# <mode> <input> <key, or none>\n\
+                    control Up k\n\
+                    control Down none\n\
+                    control Back none\n\
+                    control Sideways x\n\
+                    type Up\n";
+        let keymap = Keymap::parse(text);
+        let default = Keymap::preset(Preset::Default);
+
+        assert_eq!(
+            keymap.keys_for(InputMode::Control, Input::Up),
+            [Key::Char('k')]
+        );
+        assert!(keymap.keys_for(InputMode::Control, Input::Down).is_empty());
+        //Back can not be left unbound, or there would be no way out of a view
+        assert_eq!(
+            keymap.keys_for(InputMode::Control, Input::Back),
+            default.keys_for(InputMode::Control, Input::Back)
+        );
+        assert_eq!(
+            keymap.keys_for(InputMode::Control, Input::Left),
+            default.keys_for(InputMode::Control, Input::Left)
+        );
+        assert_eq!(
+            keymap.keys_for(InputMode::Type, Input::Up),
+            default.keys_for(InputMode::Type, Input::Up)
+        );
+    }
+}
@@ -1,7 +1,18 @@
+mod blueprint;
+mod config;
+mod diagram;
+mod font_metrics;
+mod frame;
+mod glyphs;
 mod job;
+mod keymap;
+mod news;
+mod numerals;
 mod orbit;
+mod probe;
 mod rocket;
 mod sats;
+mod settings;
 mod ui;
 
 #[macro_use]
@@ -16,29 +27,54 @@ extern crate lazy_static;
 extern crate static_assertions;
 
 use debug_log::DEBUG;
+use font_metrics::Calibrated;
+use font_metrics::METRICS;
 use job::CustomerRegistry;
 use job::Job;
+use keymap::Keymap;
+use keymap::KEYMAP;
+use news::News;
+use news::Topic;
 use rocket::Component;
 use rocket::Rocket;
 use sats::SatRegistry;
+use settings::Choice;
+use settings::GlyphMode;
+use settings::Settings;
+use settings::SETTINGS;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use termion::input::MouseTerminal;
 use termion::input::TermRead;
 use termion::raw::IntoRawMode;
+use ui::palette::Command;
 use ui::UI;
 
 mod units {
-    #[derive(Clone, Copy, Debug)]
+    use crate::numerals::Number;
+    use crate::settings::Units;
+    use crate::settings::SETTINGS;
+    use std::fmt;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
     /// A mass, represented as an integer number of grams.
     pub struct Mass(u64);
     /// A specific impulse, represented as an integer number of seconds.
     #[derive(Clone, Copy, Debug)]
     pub struct Isp(u64);
     /// A volume, represented as an integer number of liters.
-    #[derive(Clone, Copy, Debug)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
     pub struct Volume(u64);
     /// A preasure, represented as an integer number of pascals.
     #[derive(Clone, Copy, Debug)]
     pub struct Preasure(u64);
+    /// A speed, represented as an integer number of meters per second.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct Speed(u64);
 
     impl Mass {
         pub fn kg(kg: u64) -> Mass {
@@ -51,33 +87,331 @@ mod units {
         }
     }
 
+    impl std::ops::Add for Mass {
+        type Output = Mass;
+
+        fn add(self, Mass(rhs): Mass) -> Mass {
+            let Mass(lhs) = self;
+            Mass(lhs + rhs)
+        }
+    }
+
+    impl std::ops::Mul<u64> for Mass {
+        type Output = Mass;
+
+        fn mul(self, rhs: u64) -> Mass {
+            let Mass(g) = self;
+            Mass(g * rhs)
+        }
+    }
+
+    impl std::ops::Add for Volume {
+        type Output = Volume;
+
+        fn add(self, Volume(rhs): Volume) -> Volume {
+            let Volume(lhs) = self;
+            Volume(lhs + rhs)
+        }
+    }
+
+    impl std::ops::Sub for Volume {
+        type Output = Volume;
+
+        fn sub(self, Volume(rhs): Volume) -> Volume {
+            let Volume(lhs) = self;
+            Volume(lhs - rhs)
+        }
+    }
+
     impl Isp {
         pub fn s(s: u64) -> Isp {
             Isp(s)
         }
+
+        pub fn in_s(self) -> f64 {
+            let Isp(s) = self;
+            s as f64
+        }
+    }
+
+    impl Speed {
+        /// Rounds to the nearest meter per second.
+        pub fn m_per_s(m_per_s: f64) -> Speed {
+            Speed(m_per_s.round() as u64)
+        }
+
+        pub fn in_m_per_s(self) -> f64 {
+            let Speed(m_per_s) = self;
+            m_per_s as f64
+        }
+    }
+
+    impl std::ops::Add for Speed {
+        type Output = Speed;
+
+        fn add(self, Speed(rhs): Speed) -> Speed {
+            let Speed(lhs) = self;
+            Speed(lhs + rhs)
+        }
     }
 
     impl Volume {
+        pub fn m3(m3: u64) -> Volume {
+            Volume(1000 * m3)
+        }
+
         pub fn in_m3(self) -> f64 {
             let Volume(l) = self;
-            l as f64 * 1000.0
+            l as f64 / 1000.0
+        }
+    }
+
+    impl Preasure {
+        pub fn pa(pa: u64) -> Preasure {
+            Preasure(pa)
+        }
+    }
+
+    /// What a quantity is a measure of.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Dimension {
+        Mass,
+        Volume,
+    }
+
+    /// A mass or a volume, read from a number followed by its unit, such as "12 m³".
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Quantity {
+        Mass(Mass),
+        Volume(Volume),
+    }
+
+    impl Quantity {
+        /// Each unit a quantity can be typed in, with the ways of typing it and how many grams or
+        /// liters it is. A unit comes before any it ends with, so "kg" is not read as "g".
+        const UNITS: [(&'static [&'static str], Dimension, f64); 7] = [
+            (&["kg"], Dimension::Mass, 1000.0),
+            (&["lb"], Dimension::Mass, 453.592_37),
+            (&["g"], Dimension::Mass, 1.0),
+            (&["t"], Dimension::Mass, 1_000_000.0),
+            (&["m³", "m3"], Dimension::Volume, 1000.0),
+            (&["ft³", "ft3"], Dimension::Volume, 28.316_846_592),
+            (&["l", "L"], Dimension::Volume, 1.0),
+        ];
+
+        /// Reads a quantity, with or without a space before the unit.
+        pub fn parse(text: &str) -> Option<Quantity> {
+            let text = text.trim();
+            let (number, dimension, size) =
+                Quantity::UNITS
+                    .iter()
+                    .find_map(|&(spellings, dimension, size)| {
+                        let number = spellings.iter().find_map(|unit| text.strip_suffix(unit))?;
+                        Some((number, dimension, size))
+                    })?;
+            let number: f64 = number.trim_end().parse().ok()?;
+            if number < 0.0 || !number.is_finite() {
+                return None;
+            }
+            let amount = (number * size).round() as u64;
+            Some(match dimension {
+                Dimension::Mass => Quantity::Mass(Mass(amount)),
+                Dimension::Volume => Quantity::Volume(Volume(amount)),
+            })
+        }
+
+        pub fn dimension(self) -> Dimension {
+            match self {
+                Quantity::Mass(_) => Dimension::Mass,
+                Quantity::Volume(_) => Dimension::Volume,
+            }
+        }
+
+        /// The units a quantity of `dimension` can be typed in, each as it is usually written.
+        pub fn units(dimension: Dimension) -> Vec<&'static str> {
+            Quantity::UNITS
+                .iter()
+                .filter(|&&(_, d, _)| d == dimension)
+                .map(|&(spellings, _, _)| spellings[0])
+                .collect()
+        }
+    }
+
+    /// Displays in the units chosen in the settings.
+    impl fmt::Display for Mass {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let units = SETTINGS.lock().unwrap().units;
+            match units {
+                Units::Metric => write!(f, "{} kg", Number::new(self.in_kg())),
+                Units::Imperial => write!(f, "{} lb", Number::rounded(self.in_kg() * 2.204_623, 1)),
+            }
+        }
+    }
+
+    /// Displays in the units chosen in the settings.
+    impl fmt::Display for Volume {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let units = SETTINGS.lock().unwrap().units;
+            match units {
+                Units::Metric => write!(f, "{} m³", Number::new(self.in_m3())),
+                Units::Imperial => {
+                    write!(f, "{} ft³", Number::rounded(self.in_m3() * 35.314_667, 1))
+                }
+            }
+        }
+    }
+
+    /// Displays in the units chosen in the settings.
+    impl fmt::Display for Speed {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let units = SETTINGS.lock().unwrap().units;
+            match units {
+                Units::Metric => write!(f, "{} m/s", Number::new(self.in_m_per_s())),
+                Units::Imperial => {
+                    write!(
+                        f,
+                        "{} ft/s",
+                        Number::rounded(self.in_m_per_s() * 3.280_84, 0)
+                    )
+                }
+            }
         }
     }
 }
+/// How often the terminal size is checked, as termion has no way to wait for `SIGWINCH`.
+const RESIZE_POLL: Duration = Duration::from_millis(200);
+
+/// Options given on the command line.
+struct Args {
+    glyph_mode: Option<GlyphMode>,
+}
+
+impl Args {
+    const USAGE: &'static str =
+        "Usage: cuneiforbits [--ascii] [--glyphs auto|cuneiform|unicode|ascii]";
+
+    fn parse() -> Result<Args, String> {
+        let mut args = Args { glyph_mode: None };
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--ascii" => args.glyph_mode = Some(GlyphMode::Ascii),
+                "--glyphs" => {
+                    let mode = iter.next().ok_or("--glyphs needs a mode")?;
+                    let mode = GlyphMode::parse(&mode)
+                        .ok_or_else(|| format!("Unknown glyph mode: {}", mode))?;
+                    args.glyph_mode = Some(mode);
+                }
+                "-h" | "--help" => {
+                    println!("{}", Args::USAGE);
+                    std::process::exit(0);
+                }
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+        Ok(args)
+    }
+}
+
 fn main() {
-    let stdin = std::io::stdin();
-    let raw = std::io::stdout().into_raw_mode().unwrap();
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, Args::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    *SETTINGS.lock().unwrap() = Settings::load();
+    *KEYMAP.lock().unwrap() = Keymap::load();
+    if let Some(mode) = args.glyph_mode {
+        SETTINGS.lock().unwrap().glyph_mode = mode;
+    }
+
+    let mut raw = MouseTerminal::from(std::io::stdout().into_raw_mode().unwrap());
+
+    //Probing reads the terminal's answers from stdin, so it has to happen before events do
+    let input = probe::Input::spawn();
+    let term = std::env::var("TERM").unwrap_or_default();
+    let calibrated = match Calibrated::load(&term) {
+        Some(calibrated) => Some(calibrated),
+        None => probe::calibrate(&input, &mut raw).inspect(|calibrated| {
+            if let Err(e) = calibrated.save(&term) {
+                DEBUG.log(&format!("Could not save glyph widths: {}", e));
+            }
+        }),
+    };
+    if let Some(calibrated) = calibrated {
+        *METRICS.lock().unwrap() = Box::new(calibrated);
+    }
+    if SETTINGS.lock().unwrap().glyph_mode == GlyphMode::Auto {
+        let detected = probe::detect_glyph_mode(&input, &mut raw);
+        SETTINGS.lock().unwrap().detected_glyphs = detected;
+        DEBUG.log(&format!("Detected {} glyphs", detected.name()));
+    }
+
+    let (events, events_rx) = mpsc::channel();
+    thread::spawn(move || {
+        for event in input.events() {
+            if events.send(event).is_err() {
+                break;
+            }
+        }
+    });
 
     let mut ui = UI::new();
     ui.start();
+    ui.present();
+
+    let mut last_tick = Instant::now();
+    let mut size = termion::terminal_size().ok();
+    loop {
+        let tick_interval = SETTINGS.lock().unwrap().tick_speed.interval();
+        let timeout = match tick_interval {
+            Some(interval) => interval
+                .checked_sub(last_tick.elapsed())
+                .unwrap_or_default()
+                .min(RESIZE_POLL),
+            None => RESIZE_POLL,
+        };
+        let mut changed = false;
+        match events_rx.recv_timeout(timeout) {
+            Ok(event) => {
+                let event = event.unwrap();
+                if !ui.input(&event) {
+                    break;
+                }
+                DEBUG.on_event(&event);
+                changed = true;
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if matches!(tick_interval, Some(interval) if last_tick.elapsed() >= interval) {
+                    GAME.tick();
+                    DEBUG.log("Auto ticked");
+                    last_tick = Instant::now();
+                    ui.redraw();
+                    changed = true;
+                } else if ui.toast_changed() {
+                    ui.redraw();
+                    changed = true;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let new_size = termion::terminal_size().ok();
+        if new_size != size {
+            size = new_size;
+            ui.redraw();
+            changed = true;
+        }
 
-    for event in stdin.events() {
-        let event = event.unwrap();
-        if !ui.input(&event) {
-            break;
+        if changed {
+            if SETTINGS.lock().unwrap().debug_overlay {
+                DEBUG.redraw();
+            }
+            ui.present();
         }
-        DEBUG.on_event(&event);
-        DEBUG.redraw();
     }
 
     drop(ui); //ui should be dropped before the terminal exits raw mode
@@ -96,10 +430,9 @@ pub struct Game {
     available_jobs: Mutex<Vec<Job>>,
     accepted_jobs: Mutex<Vec<Job>>,
     known_components: Mutex<Vec<Component>>,
+    news: News,
 }
 
-const TARGET_JOBS: usize = 3;
-
 impl Game {
     fn new() -> Game {
         Game {
@@ -109,15 +442,20 @@ impl Game {
             available_jobs: Mutex::new(Vec::new()),
             accepted_jobs: Mutex::new(Vec::new()),
             known_components: Mutex::new(rocket::INITIAL_KNOWN_COMPONENTS.to_vec()),
+            news: News::new(),
         }
     }
 
     fn tick(&self) {
         let mut jobs = self.available_jobs.lock().unwrap();
-        if jobs.len() < TARGET_JOBS {
-            jobs.push(Job::generate());
+        if jobs.len() < SETTINGS.lock().unwrap().difficulty.target_jobs() {
+            let job = Job::generate();
+            let customer = self.customers.on(job.customer, |c| c.name.clone());
+            let text = format!("New job from {}", customer.unwrap_or_default());
+            jobs.push(job);
+            drop(jobs);
+            self.news.post(Topic::Job, text);
         }
-        drop(jobs);
     }
 
     fn accept_job_at(&self, idx: usize) {
@@ -131,6 +469,55 @@ impl Game {
     fn decline_job_at(&self, idx: usize) {
         let _ = self.available_jobs.lock().unwrap().remove(idx);
     }
+
+    /// The commands the palette offers for the game itself.
+    fn commands() -> Vec<Command> {
+        vec![
+            Command::anywhere("tick", Some("times"), |times| {
+                const MAX_TIMES: u32 = 1000;
+                let times = times.unwrap_or(1);
+                if times > MAX_TIMES {
+                    return Err(format!("Tick at most {} times at once", MAX_TIMES));
+                }
+                for _ in 0..times {
+                    GAME.tick();
+                }
+                Ok(None)
+            }),
+            Command::anywhere("accept job", Some("number"), |number| {
+                GAME.accept_job_at(GAME.job_idx(number)?);
+                Ok(None)
+            }),
+            Command::anywhere("decline job", Some("number"), |number| {
+                GAME.decline_job_at(GAME.job_idx(number)?);
+                Ok(None)
+            }),
+        ]
+    }
+
+    /// Where the available job with the number the player typed is, counting from 1 as the jobs
+    /// are listed.
+    fn job_idx(&self, number: Option<u32>) -> Result<usize, String> {
+        let number = number.ok_or("Type the number of the job after the command")?;
+        let len = self.available_jobs.lock().unwrap().len();
+        match (number as usize).checked_sub(1) {
+            Some(idx) if idx < len => Ok(idx),
+            _ => Err(format!("There is no job {}", number)),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Game {
+    /// Puts everything back to how a new game starts, as tests share the one game.
+    fn reset(&self) {
+        self.customers.reset();
+        self.rocket_designs.lock().unwrap().clear();
+        self.available_jobs.lock().unwrap().clear();
+        self.accepted_jobs.lock().unwrap().clear();
+        *self.known_components.lock().unwrap() = rocket::INITIAL_KNOWN_COMPONENTS.to_vec();
+        self.news.reset();
+    }
 }
 
 assert_impl_all!(Game: Sync);
@@ -0,0 +1,83 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Everything the player has been told about, oldest first.
+#[derive(Debug)]
+pub struct News {
+    notices: Mutex<Vec<Notice>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Notice {
+    pub topic: Topic,
+    pub text: String,
+    posted: Instant,
+}
+
+/// What a notice is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(dead_code)] //Only jobs are posted about until there are launches, deadlines and research
+pub enum Topic {
+    Job,
+    Launch,
+    Deadline,
+    Research,
+}
+
+impl News {
+    /// How long a notice is shown on every view after it is posted.
+    const TOAST_TIME: Duration = Duration::from_secs(4);
+
+    pub fn new() -> News {
+        News {
+            notices: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn post<S: Into<String>>(&self, topic: Topic, text: S) {
+        self.notices.lock().unwrap().push(Notice {
+            topic,
+            text: text.into(),
+            posted: Instant::now(),
+        });
+    }
+
+    /// The latest notice and its index, if it was posted recently enough to show as a toast.
+    pub fn toast(&self) -> Option<(usize, Notice)> {
+        let notices = self.notices.lock().unwrap();
+        let notice = notices.last()?;
+        if notice.posted.elapsed() < News::TOAST_TIME {
+            Some((notices.len() - 1, notice.clone()))
+        } else {
+            None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.notices.lock().unwrap().len()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<Notice> {
+        self.notices.lock().unwrap().get(idx).cloned()
+    }
+}
+
+#[cfg(test)]
+impl News {
+    /// Forgets every notice, as in a new game.
+    pub fn reset(&self) {
+        self.notices.lock().unwrap().clear();
+    }
+}
+
+impl Topic {
+    pub fn name(self) -> &'static str {
+        match self {
+            Topic::Job => "Job",
+            Topic::Launch => "Launch",
+            Topic::Deadline => "Deadline",
+            Topic::Research => "Research",
+        }
+    }
+}
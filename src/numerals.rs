@@ -0,0 +1,190 @@
+use crate::settings::GlyphMode;
+use crate::settings::Numerals;
+use crate::settings::SETTINGS;
+use std::fmt;
+
+/// A quantity to show to the player, in the numerals chosen in the settings.
+#[derive(Clone, Copy, Debug)]
+pub struct Number {
+    value: f64,
+    /// Digits after the point, or `None` for as many as the value needs.
+    decimals: Option<usize>,
+}
+
+/// Signs for the units of a sexagesimal digit, from one to nine.
+const ONES: [&str; 9] = ["𒁹", "𒈫", "𒐈", "𒐉", "𒐊", "𒐋", "𒐌", "𒐍", "𒐎"];
+/// Signs for the tens of a sexagesimal digit, from ten to fifty.
+const TENS: [&str; 5] = ["𒌋", "𒎙", "𒌍", "𒐏", "𒐐"];
+/// Marks an empty place between digits, as late Babylonian scribes did.
+const ZERO: &str = "𒑲";
+/// Separates the whole part from the fraction.
+const POINT: &str = "𒑱";
+
+/// The most sexagesimal places shown after the point, as sixtieths and three thousand six
+/// hundredths.
+const MAX_PLACES: usize = 2;
+
+impl Number {
+    pub fn new(value: f64) -> Number {
+        Number {
+            value,
+            decimals: None,
+        }
+    }
+
+    /// Rounds to `decimals` digits after the point, or as many sexagesimal places.
+    pub fn rounded(value: f64, decimals: usize) -> Number {
+        Number {
+            value,
+            decimals: Some(decimals),
+        }
+    }
+
+    fn fmt_decimal(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.decimals {
+            Some(decimals) => write!(f, "{:.*}", decimals, self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+
+    /// Writes the number in base 60, in cuneiform or, for terminals that can not show it, the
+    /// modern `1,23;30` notation.
+    fn fmt_sexagesimal(&self, f: &mut fmt::Formatter, cuneiform: bool) -> fmt::Result {
+        let places = self.decimals.unwrap_or(MAX_PLACES).min(MAX_PLACES);
+        let scale = 60u64.pow(places as u32);
+        let scaled = (self.value.abs() * scale as f64).round() as u64;
+        let (whole, mut fraction) = (scaled / scale, scaled % scale);
+
+        if self.value < 0.0 && scaled != 0 {
+            f.write_str("-")?;
+        }
+
+        let mut digits = Vec::new();
+        let mut rest = whole;
+        loop {
+            digits.push(rest % 60);
+            rest /= 60;
+            if rest == 0 {
+                break;
+            }
+        }
+        for (idx, &digit) in digits.iter().rev().enumerate() {
+            if idx > 0 {
+                f.write_str(if cuneiform { " " } else { "," })?;
+            }
+            write_digit(f, digit, cuneiform)?;
+        }
+
+        if fraction == 0 {
+            return Ok(());
+        }
+        f.write_str(if cuneiform { POINT } else { ";" })?;
+        let mut place = scale / 60;
+        let mut first = true;
+        while fraction != 0 {
+            if !first {
+                f.write_str(if cuneiform { " " } else { "," })?;
+            }
+            write_digit(f, fraction / place, cuneiform)?;
+            fraction %= place;
+            place /= 60;
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+fn write_digit(f: &mut fmt::Formatter, digit: u64, cuneiform: bool) -> fmt::Result {
+    if !cuneiform {
+        return write!(f, "{}", digit);
+    }
+    if digit == 0 {
+        return f.write_str(ZERO);
+    }
+    let (tens, ones) = (digit / 10, digit % 10);
+    if tens > 0 {
+        f.write_str(TENS[tens as usize - 1])?;
+    }
+    if ones > 0 {
+        f.write_str(ONES[ones as usize - 1])?;
+    }
+    Ok(())
+}
+
+/// Displays in the numerals chosen in the settings, followed by the decimal value while details
+/// are shown.
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (numerals, show_decimal, glyphs) = {
+            let settings = SETTINGS.lock().unwrap();
+            (settings.numerals, settings.show_decimal, settings.glyphs())
+        };
+        match numerals {
+            Numerals::Decimal => self.fmt_decimal(f),
+            Numerals::Sexagesimal => {
+                self.fmt_sexagesimal(f, glyphs == GlyphMode::Cuneiform)?;
+                if show_decimal {
+                    f.write_str(" (")?;
+                    self.fmt_decimal(f)?;
+                    f.write_str(")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `number` in base 60 without reading the settings.
+    struct Sexagesimal(Number, bool);
+
+    impl fmt::Display for Sexagesimal {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            self.0.fmt_sexagesimal(f, self.1)
+        }
+    }
+
+    fn modern(number: Number) -> String {
+        Sexagesimal(number, false).to_string()
+    }
+
+    #[test]
+    fn sexagesimal_places() {
+        assert_eq!(modern(Number::new(0.0)), "0");
+        assert_eq!(modern(Number::new(59.0)), "59");
+        assert_eq!(modern(Number::new(61.0)), "1,1");
+        assert_eq!(modern(Number::new(3600.0)), "1,0,0");
+        assert_eq!(modern(Number::new(-90.0)), "-1,30");
+    }
+
+    #[test]
+    fn sexagesimal_fractions() {
+        assert_eq!(modern(Number::new(1.5)), "1;30");
+        assert_eq!(modern(Number::new(1.0 / 3.0)), "0;20");
+        assert_eq!(modern(Number::new(1.0 / 120.0)), "0;0,30");
+        //A seventh does not end in base 60, so it is cut off at the second place
+        assert_eq!(modern(Number::new(1.0 / 7.0)), "0;8,34");
+        assert_eq!(modern(Number::rounded(1.5, 0)), "2");
+        assert_eq!(modern(Number::rounded(1.0 / 120.0, 1)), "0;1");
+    }
+
+    #[test]
+    fn sexagesimal_rounding_carries() {
+        assert_eq!(modern(Number::new(59.9999)), "1,0");
+        assert_eq!(modern(Number::rounded(3599.9, 0)), "1,0,0");
+        //Too small to show, so not shown as negative either
+        assert_eq!(modern(Number::new(-0.0001)), "0");
+    }
+
+    #[test]
+    fn cuneiform_digits() {
+        let cuneiform = |value| Sexagesimal(Number::new(value), true).to_string();
+        assert_eq!(cuneiform(0.0), ZERO);
+        assert_eq!(cuneiform(10.0), "𒌋");
+        assert_eq!(cuneiform(23.0), "𒎙𒐈");
+        assert_eq!(cuneiform(60.5), format!("𒁹 {}{}𒌍", ZERO, POINT));
+    }
+}
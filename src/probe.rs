@@ -0,0 +1,119 @@
+use crate::font_metrics::Calibrated;
+use crate::settings::GlyphMode;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+use termion::{clear, cursor};
+
+/// How long to wait for the terminal to report where the cursor is before giving up on it.
+const REPLY_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// The longest cursor report looked for, so input that never ends a report is not read forever.
+const MAX_REPLY_LEN: usize = 16;
+
+/// The bytes read from stdin, read on a thread of their own so that probes can wait for the
+/// terminal's answer with a timeout. Once probing is done it is read as events, so nothing typed
+/// while probing is lost to a reader left behind.
+pub struct Input(Receiver<u8>);
+
+impl Input {
+    /// Starts reading stdin.
+    pub fn spawn() -> Input {
+        let (bytes, bytes_rx) = mpsc::channel();
+        thread::spawn(move || {
+            for byte in io::stdin().lock().bytes() {
+                match byte {
+                    Ok(byte) if bytes.send(byte).is_ok() => {}
+                    _ => break,
+                }
+            }
+        });
+        Input(bytes_rx)
+    }
+
+    /// Asks the terminal where the cursor is, and returns its column counting from 1, or `None`
+    /// if the terminal does not answer in time.
+    fn cursor_column<W: Write>(&self, out: &mut W) -> Option<u16> {
+        write!(out, "\x1b[6n").ok()?;
+        out.flush().ok()?;
+
+        //The answer is ESC [ row ; column R
+        let mut reply = Vec::new();
+        while reply.last() != Some(&b'R') {
+            if reply.len() == MAX_REPLY_LEN {
+                return None;
+            }
+            reply.push(self.0.recv_timeout(REPLY_TIMEOUT).ok()?);
+        }
+        let reply = std::str::from_utf8(&reply).ok()?;
+        let report = &reply[reply.rfind("\x1b[")? + 2..reply.len() - 1];
+        let (_, column) = report.split_once(';')?;
+        column.parse().ok()
+    }
+}
+
+impl Read for Input {
+    /// Waits for a byte, then takes as many more as have already arrived.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut len = 0;
+        while len < buf.len() {
+            let byte = if len == 0 {
+                self.0.recv().ok()
+            } else {
+                self.0.try_recv().ok()
+            };
+            match byte {
+                Some(byte) => buf[len] = byte,
+                None => break,
+            }
+            len += 1;
+        }
+        Ok(len)
+    }
+}
+
+/// How many columns the terminal moves the cursor on by to print `s`, found by printing it at
+/// the top left and asking the terminal where the cursor ended up.
+///
+/// `None` if the terminal does not answer. The terminal must be in raw mode.
+pub fn advance<W: Write>(input: &Input, out: &mut W, s: &str) -> Option<u16> {
+    write!(out, "{}{}", cursor::Goto(1, 1), s).ok()?;
+    input.cursor_column(out)?.checked_sub(1)
+}
+
+/// Measures how far the terminal advances the cursor for each class of glyph, or `None` if it
+/// does not answer.
+pub fn calibrate<W: Write>(input: &Input, out: &mut W) -> Option<Calibrated> {
+    let mut buf = [0; 4];
+    let calibrated = Calibrated::measure(|c| advance(input, out, c.encode_utf8(&mut buf)));
+    let _ = write!(out, "{}", clear::All);
+    calibrated
+}
+
+/// Guesses which glyph mode the terminal can show.
+///
+/// Terminals lay text out by their own width tables rather than the font, so a probe can not
+/// see cuneiform drawn as tofu. Cuneiform is only picked for Alacritty, the terminal known to
+/// work with the Cuneiforbits font, when it lays cuneiform out the way that font expects.
+pub fn detect_glyph_mode<W: Write>(input: &Input, out: &mut W) -> GlyphMode {
+    let cuneiform = advance(input, out, "𒀀");
+    let symbol = advance(input, out, "▶");
+    let _ = write!(out, "{}", clear::All);
+
+    match (cuneiform, symbol) {
+        (None, _) | (_, None) => GlyphMode::Ascii,
+        (Some(1), _) if is_alacritty() => GlyphMode::Cuneiform,
+        (_, Some(1)) | (_, Some(2)) => GlyphMode::Unicode,
+        _ => GlyphMode::Ascii,
+    }
+}
+
+fn is_alacritty() -> bool {
+    matches!(std::env::var("TERM"), Ok(term) if term.starts_with("alacritty"))
+        || std::env::var_os("ALACRITTY_WINDOW_ID").is_some()
+        || std::env::var_os("ALACRITTY_SOCKET").is_some()
+}
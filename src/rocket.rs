@@ -1,11 +1,16 @@
 #![allow(dead_code)] //temp
 
+use crate::font_metrics;
+use crate::glyphs::Glyph;
 use crate::units::*;
 use std::fmt;
 
 pub type Crewed = bool;
 
-#[derive(Clone, Copy, Debug)]
+/// Standard gravity in m/s², which turns a specific impulse into an exhaust velocity.
+const G0: f64 = 9.806_65;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PropellantType {
     Hyrdolox,
     Methalox,
@@ -20,7 +25,7 @@ pub enum Fuel {
     RP1,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CryoClass {
     STP,
     Cryo,
@@ -30,15 +35,44 @@ pub enum CryoClass {
 #[derive(Clone, Copy, Debug)]
 pub enum ComponentClass {
     Engine(PropellantType, Isp),
-    Tank(CryoClass, Preasure),
+    /// The mass is of the propellant it holds when full, on top of the tank's own.
+    Tank(CryoClass, Preasure, Mass),
     Fairing(Volume),
     Capsule(Crewed, Volume, Volume),
+    /// Separates stages, or attaches side boosters.
+    Decoupler(Mount),
+}
+
+/// Where a decoupler holds what comes after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mount {
+    /// Below, as the next stage.
+    Inline,
+    /// Beside, as a pair of side boosters.
+    Radial,
+}
+
+/// How big a fairing is, which sets the glyph it is drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Size {
+    Small,
+    Large,
+}
+
+/// The parts of a `ComponentClass` that decide its glyph, without the figures that only matter
+/// to the simulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Engine(PropellantType),
+    Tank(CryoClass),
+    Fairing(Size),
+    Capsule(Crewed),
+    Decoupler(Mount),
 }
 
 #[derive(Clone, Debug)]
 pub struct Component {
     pub name: String,
-    pub display: String,
     pub mass: Mass,
     pub class: ComponentClass,
 }
@@ -52,7 +86,7 @@ pub struct Rocket {
 impl fmt::Display for Rocket {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for component in &self.components {
-            write!(f, "{}", component.display)?
+            write!(f, "{}", component)?
         }
         Ok(())
     }
@@ -60,7 +94,7 @@ impl fmt::Display for Rocket {
 
 impl fmt::Display for Component {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.display)
+        write!(f, "{}", self.class.symbol().get())
     }
 }
 
@@ -71,50 +105,256 @@ impl Rocket {
             components: Vec::new(),
         }
     }
+
+    pub fn mass(&self) -> Mass {
+        self.components
+            .iter()
+            .fold(Mass::kg(0), |total, component| total + component.mass)
+    }
+
+    /// How much payload fits under the fairings.
+    pub fn payload_volume(&self) -> Volume {
+        self.components
+            .iter()
+            .fold(Volume::m3(0), |total, component| match component.class {
+                ComponentClass::Fairing(volume) => total + volume,
+                _ => total,
+            })
+    }
+
+    /// One more than the number of stage decouplers.
+    pub fn stage_count(&self) -> usize {
+        1 + self
+            .components
+            .iter()
+            .filter(|c| matches!(c.class, ComponentClass::Decoupler(Mount::Inline)))
+            .count()
+    }
+
+    /// The delta-v of each stage, in the order they fire from the bottom up. A stage burns all
+    /// of its propellant while pushing every stage above it, full, along with its own empty
+    /// mass, as the rocket equation has it. Each stage decoupler falls away with the stage below
+    /// it, and a stage with more than one engine gets their average specific impulse.
+    pub fn stage_delta_v(&self) -> Vec<Speed> {
+        let mut stages: Vec<Vec<&Component>> = vec![Vec::new()];
+        for component in &self.components {
+            if let ComponentClass::Decoupler(Mount::Inline) = component.class {
+                stages.push(Vec::new());
+            }
+            stages.last_mut().unwrap().push(component);
+        }
+
+        let mut above = 0.0;
+        let mut delta_v = Vec::new();
+        for stage in stages {
+            let empty = above + stage.iter().map(|c| c.mass.in_kg()).sum::<f64>();
+            let full = empty
+                + stage
+                    .iter()
+                    .map(|c| c.class.propellant().in_kg())
+                    .sum::<f64>();
+            let isps: Vec<f64> = stage
+                .iter()
+                .filter_map(|c| match c.class {
+                    ComponentClass::Engine(_, isp) => Some(isp.in_s()),
+                    _ => None,
+                })
+                .collect();
+            let isp = isps.iter().sum::<f64>() / isps.len().max(1) as f64;
+            //A stage of nothing weighs nothing, and gets nowhere
+            let ratio = if empty > 0.0 { full / empty } else { 1.0 };
+            delta_v.push(Speed::m_per_s(isp * G0 * ratio.ln()));
+            above = full;
+        }
+        delta_v.reverse();
+        delta_v
+    }
 }
 
 impl ComponentClass {
-    pub fn symbol(&self) -> String {
-        //TODO pick nice unicode sybols for these
+    /// The propellant the component holds when full.
+    pub fn propellant(&self) -> Mass {
+        match *self {
+            ComponentClass::Tank(_, _, propellant) => propellant,
+            _ => Mass::kg(0),
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        match *self {
+            ComponentClass::Engine(propellant, _) => Kind::Engine(propellant),
+            ComponentClass::Tank(cryo, _, _) => Kind::Tank(cryo),
+            ComponentClass::Fairing(volume) => Kind::Fairing(Size::of(volume)),
+            ComponentClass::Capsule(crewed, _, _) => Kind::Capsule(crewed),
+            ComponentClass::Decoupler(mount) => Kind::Decoupler(mount),
+        }
+    }
+
+    pub fn symbol(&self) -> Glyph {
+        self.kind().symbol()
+    }
+}
+
+impl Size {
+    /// Fairings that hold more than this are large.
+    const SMALL_VOLUME_M3: f64 = 10.0;
+
+    pub fn of(volume: Volume) -> Size {
+        if volume.in_m3() > Size::SMALL_VOLUME_M3 {
+            Size::Large
+        } else {
+            Size::Small
+        }
+    }
+}
+
+impl Kind {
+    /// Every kind, in the order the legend lists them.
+    pub const ALL: [Kind; 13] = [
+        Kind::Engine(PropellantType::Hyrdolox),
+        Kind::Engine(PropellantType::Methalox),
+        Kind::Engine(PropellantType::Keralox),
+        Kind::Engine(PropellantType::Hypergolic),
+        Kind::Tank(CryoClass::STP),
+        Kind::Tank(CryoClass::Cryo),
+        Kind::Tank(CryoClass::SuperCryo),
+        Kind::Fairing(Size::Small),
+        Kind::Fairing(Size::Large),
+        Kind::Capsule(true),
+        Kind::Capsule(false),
+        Kind::Decoupler(Mount::Inline),
+        Kind::Decoupler(Mount::Radial),
+    ];
+
+    /// The glyph scheme. Engines are named for what they burn, tanks are vessels, fairings
+    /// are coverings, capsules are people or houses and decouplers split things, with
+    /// triangles, bars and circles standing in outside of cuneiform.
+    pub fn symbol(self) -> Glyph {
+        let (cuneiform, unicode, ascii) = match self {
+            Kind::Engine(PropellantType::Hyrdolox) => ("𒀀", "▼", "H"),
+            Kind::Engine(PropellantType::Methalox) => ("𒉈", "▽", "M"),
+            Kind::Engine(PropellantType::Keralox) => ("𒉌", "▾", "K"),
+            Kind::Engine(PropellantType::Hypergolic) => ("𒌓", "▿", "Y"),
+            Kind::Tank(CryoClass::STP) => ("𒂁", "▮", "t"),
+            Kind::Tank(CryoClass::Cryo) => ("𒀊", "▯", "T"),
+            Kind::Tank(CryoClass::SuperCryo) => ("𒂂", "▣", "S"),
+            Kind::Fairing(Size::Small) => ("𒌆", "▴", "^"),
+            Kind::Fairing(Size::Large) => ("𒌇", "▲", "A"),
+            Kind::Capsule(true) => ("𒇽", "●", "C"),
+            Kind::Capsule(false) => ("𒂍", "○", "O"),
+            Kind::Decoupler(Mount::Inline) => ("𒁇", "▬", "="),
+            Kind::Decoupler(Mount::Radial) => ("𒋻", "◫", "+"),
+        };
+        Glyph {
+            cuneiform,
+            unicode,
+            ascii,
+        }
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Size::Small => write!(f, "Small"),
+            Size::Large => write!(f, "Large"),
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ComponentClass::Engine(_, _) => "E",
-            ComponentClass::Tank(_, _) => "T",
-            ComponentClass::Fairing(_) => "F",
-            ComponentClass::Capsule(_, _, _) => "C",
+            Kind::Engine(PropellantType::Hyrdolox) => write!(f, "Hydrolox engine"),
+            Kind::Engine(PropellantType::Methalox) => write!(f, "Methalox engine"),
+            Kind::Engine(PropellantType::Keralox) => write!(f, "Keralox engine"),
+            Kind::Engine(PropellantType::Hypergolic) => write!(f, "Hypergolic engine"),
+            Kind::Tank(CryoClass::STP) => write!(f, "Room temperature tank"),
+            Kind::Tank(CryoClass::Cryo) => write!(f, "Cryogenic tank"),
+            Kind::Tank(CryoClass::SuperCryo) => write!(f, "Deep cryogenic tank"),
+            Kind::Fairing(size) => write!(f, "{} fairing", size),
+            Kind::Capsule(true) => write!(f, "Crewed capsule"),
+            Kind::Capsule(false) => write!(f, "Uncrewed capsule"),
+            Kind::Decoupler(Mount::Inline) => write!(f, "Stage decoupler"),
+            Kind::Decoupler(Mount::Radial) => write!(f, "Booster decoupler"),
         }
-        .to_string()
     }
 }
 
 impl Component {
-    pub const MAX_WIDTH: u16 = 5; //picked out of thin air
+    /// The number of columns the component takes up when displayed.
+    pub fn width(&self) -> u16 {
+        font_metrics::width(&self.to_string())
+    }
+
+    /// The widest of `components`, so they can be lined up in columns.
+    pub fn max_width<'a, I: IntoIterator<Item = &'a Component>>(components: I) -> u16 {
+        components
+            .into_iter()
+            .map(Component::width)
+            .max()
+            .unwrap_or(0)
+    }
 }
 
 lazy_static! {
     pub static ref INITIAL_KNOWN_COMPONENTS: Vec<Component> = vec![
         Component {
             name: "Foo".to_string(),
-            display: "Foo".to_string(),
             mass: Mass::kg(12),
             class: ComponentClass::Engine(PropellantType::Hyrdolox, Isp::s(3)),
         },
         Component {
             name: "Bar".to_string(),
-            display: "Foo".to_string(),
             mass: Mass::kg(12),
-            class: ComponentClass::Engine(PropellantType::Hyrdolox, Isp::s(3)),
+            class: ComponentClass::Tank(CryoClass::Cryo, Preasure::pa(3), Mass::kg(100)),
         },
         Component {
             name: "Baz".to_string(),
-            display: "Foo".to_string(),
             mass: Mass::kg(12),
-            class: ComponentClass::Engine(PropellantType::Hyrdolox, Isp::s(3)),
+            class: ComponentClass::Fairing(Volume::m3(3)),
         },
         Component {
             name: "Quux".to_string(),
-            display: "Foo".to_string(),
             mass: Mass::kg(12),
-            class: ComponentClass::Engine(PropellantType::Hyrdolox, Isp::s(3)),
+            class: ComponentClass::Capsule(true, Volume::m3(3), Volume::m3(1)),
+        },
+        Component {
+            name: "Corge".to_string(),
+            mass: Mass::kg(2),
+            class: ComponentClass::Decoupler(Mount::Inline),
+        },
+        Component {
+            name: "Grault".to_string(),
+            mass: Mass::kg(2),
+            class: ComponentClass::Decoupler(Mount::Radial),
         },
     ];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The first two of `components` drawn with the same glyph in any glyph mode, which would
+    /// make rockets built from them ambiguous.
+    fn shared_symbol(components: &[Component]) -> Option<(&Component, &Component)> {
+        for (idx, a) in components.iter().enumerate() {
+            for b in &components[idx + 1..] {
+                let (a_glyph, b_glyph) = (a.class.symbol(), b.class.symbol());
+                if a_glyph.cuneiform == b_glyph.cuneiform
+                    || a_glyph.unicode == b_glyph.unicode
+                    || a_glyph.ascii == b_glyph.ascii
+                {
+                    return Some((a, b));
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn catalog_glyphs_are_unique() {
+        assert!(shared_symbol(&INITIAL_KNOWN_COMPONENTS).is_none());
+    }
+}
@@ -0,0 +1,316 @@
+use crate::config;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::Mutex;
+use std::time::Duration;
+
+lazy_static! {
+    pub static ref SETTINGS: Mutex<Settings> = Mutex::new(Settings::default());
+}
+
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub units: Units,
+    pub difficulty: Difficulty,
+    pub tick_speed: TickSpeed,
+    pub debug_overlay: bool,
+    pub glyph_mode: GlyphMode,
+    pub numerals: Numerals,
+    /// Whether sexagesimal numbers are followed by their decimal value. Not saved.
+    pub show_decimal: bool,
+    /// What `GlyphMode::Auto` stands for, as detected at startup. Not saved.
+    pub detected_glyphs: GlyphMode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Units {
+    Metric,
+    Imperial,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TickSpeed {
+    Off,
+    Slow,
+    Normal,
+    Fast,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlyphMode {
+    /// Whichever of the others the terminal was detected to support.
+    Auto,
+    Cuneiform,
+    /// Common Unicode symbols in place of cuneiform.
+    Unicode,
+    Ascii,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Numerals {
+    Decimal,
+    /// Base 60, as the Babylonians counted.
+    Sexagesimal,
+}
+
+/// A setting that is one of a fixed list of values, which can be cycled through and is stored
+/// in the config file by name.
+pub trait Choice: Copy + PartialEq + 'static {
+    const ALL: &'static [Self];
+
+    fn name(self) -> &'static str;
+
+    fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&c| c == self).unwrap();
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    fn prev(self) -> Self {
+        let idx = Self::ALL.iter().position(|&c| c == self).unwrap();
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name().eq_ignore_ascii_case(s))
+    }
+}
+
+impl Choice for Units {
+    const ALL: &'static [Units] = &[Units::Metric, Units::Imperial];
+
+    fn name(self) -> &'static str {
+        match self {
+            Units::Metric => "Metric",
+            Units::Imperial => "Imperial",
+        }
+    }
+}
+
+impl Choice for Difficulty {
+    const ALL: &'static [Difficulty] = &[Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+impl Choice for TickSpeed {
+    const ALL: &'static [TickSpeed] = &[
+        TickSpeed::Off,
+        TickSpeed::Slow,
+        TickSpeed::Normal,
+        TickSpeed::Fast,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            TickSpeed::Off => "Off",
+            TickSpeed::Slow => "Slow",
+            TickSpeed::Normal => "Normal",
+            TickSpeed::Fast => "Fast",
+        }
+    }
+}
+
+impl Choice for GlyphMode {
+    const ALL: &'static [GlyphMode] = &[
+        GlyphMode::Auto,
+        GlyphMode::Cuneiform,
+        GlyphMode::Unicode,
+        GlyphMode::Ascii,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            GlyphMode::Auto => "Auto",
+            GlyphMode::Cuneiform => "Cuneiform",
+            GlyphMode::Unicode => "Unicode",
+            GlyphMode::Ascii => "ASCII",
+        }
+    }
+}
+
+impl Choice for Numerals {
+    const ALL: &'static [Numerals] = &[Numerals::Decimal, Numerals::Sexagesimal];
+
+    fn name(self) -> &'static str {
+        match self {
+            Numerals::Decimal => "Decimal",
+            Numerals::Sexagesimal => "Sexagesimal",
+        }
+    }
+}
+
+impl Choice for bool {
+    const ALL: &'static [bool] = &[false, true];
+
+    fn name(self) -> &'static str {
+        if self {
+            "On"
+        } else {
+            "Off"
+        }
+    }
+}
+
+impl Difficulty {
+    /// The number of jobs the game tries to keep on offer.
+    pub fn target_jobs(self) -> usize {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 2,
+        }
+    }
+
+    /// The number of customers jobs are drawn from.
+    pub fn target_customers(self) -> u8 {
+        match self {
+            Difficulty::Easy => 3,
+            Difficulty::Normal => 5,
+            Difficulty::Hard => 8,
+        }
+    }
+}
+
+impl TickSpeed {
+    /// How often the game ticks on its own, or `None` if it only ticks when asked to.
+    pub fn interval(self) -> Option<Duration> {
+        match self {
+            TickSpeed::Off => None,
+            TickSpeed::Slow => Some(Duration::from_secs(10)),
+            TickSpeed::Normal => Some(Duration::from_secs(5)),
+            TickSpeed::Fast => Some(Duration::from_secs(2)),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            units: Units::Metric,
+            difficulty: Difficulty::Normal,
+            tick_speed: TickSpeed::Off,
+            debug_overlay: true,
+            glyph_mode: GlyphMode::Auto,
+            numerals: Numerals::Decimal,
+            show_decimal: false,
+            detected_glyphs: GlyphMode::Cuneiform,
+        }
+    }
+}
+
+impl Settings {
+    const FILE_NAME: &'static str = "settings";
+
+    /// The glyph mode in use, which is never `Auto`.
+    pub fn glyphs(&self) -> GlyphMode {
+        match self.glyph_mode {
+            GlyphMode::Auto => self.detected_glyphs,
+            mode => mode,
+        }
+    }
+
+    /// Loads the settings from the config file, falling back to the defaults for anything that
+    /// is missing or malformed.
+    pub fn load() -> Settings {
+        Settings::parse(&config::read(Settings::FILE_NAME).unwrap_or_default())
+    }
+
+    fn parse(text: &str) -> Settings {
+        let mut settings = Settings::default();
+        for (key, value) in config::lines(text).filter_map(config::key_value) {
+            settings.set(key, value);
+        }
+        settings
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        fn parse_into<T: Choice>(field: &mut T, value: &str) {
+            if let Some(v) = T::parse(value) {
+                *field = v;
+            }
+        }
+
+        match key {
+            "units" => parse_into(&mut self.units, value),
+            "difficulty" => parse_into(&mut self.difficulty, value),
+            "tick_speed" => parse_into(&mut self.tick_speed, value),
+            "debug_overlay" => parse_into(&mut self.debug_overlay, value),
+            "glyph_mode" => parse_into(&mut self.glyph_mode, value),
+            "numerals" => parse_into(&mut self.numerals, value),
+            _ => {}
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        config::save(Settings::FILE_NAME, &self.to_text())
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "units = {}", self.units.name());
+        let _ = writeln!(text, "difficulty = {}", self.difficulty.name());
+        let _ = writeln!(text, "tick_speed = {}", self.tick_speed.name());
+        let _ = writeln!(text, "debug_overlay = {}", self.debug_overlay.name());
+        let _ = writeln!(text, "glyph_mode = {}", self.glyph_mode.name());
+        let _ = writeln!(text, "numerals = {}", self.numerals.name());
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = Settings {
+            units: Units::Imperial,
+            difficulty: Difficulty::Hard,
+            tick_speed: TickSpeed::Fast,
+            debug_overlay: false,
+            glyph_mode: GlyphMode::Ascii,
+            numerals: Numerals::Sexagesimal,
+            ..Settings::default()
+        };
+        let parsed = Settings::parse(&settings.to_text());
+        assert_eq!(parsed.units, settings.units);
+        assert_eq!(parsed.difficulty, settings.difficulty);
+        assert_eq!(parsed.tick_speed, settings.tick_speed);
+        assert_eq!(parsed.debug_overlay, settings.debug_overlay);
+        assert_eq!(parsed.glyph_mode, settings.glyph_mode);
+        assert_eq!(parsed.numerals, settings.numerals);
+    }
+
+    #[test]
+    fn bad_settings_lines_keep_defaults() {
+        let text = "# a comment\n\
+                    \n\
+                    units=imperial\n\
+                    difficulty = Impossible\n\
+                    tick_speed Fast\n\
+                    colour = blue\n  \
+                    numerals =   Sexagesimal  \n";
+        let parsed = Settings::parse(text);
+        let default = Settings::default();
+        assert_eq!(parsed.units, Units::Imperial);
+        assert_eq!(parsed.difficulty, default.difficulty);
+        assert_eq!(parsed.tick_speed, default.tick_speed);
+        assert_eq!(parsed.numerals, Numerals::Sexagesimal);
+    }
+}
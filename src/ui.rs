@@ -1,21 +1,48 @@
+use crate::font_metrics;
+use crate::frame::FRAME;
+use crate::glyphs;
+use crate::keymap;
+use crate::keymap::KEYMAP;
+use crate::settings::SETTINGS;
 use crate::ui_print;
-use std::io::stdout;
-use std::io::Write;
+use crate::GAME;
+use layout::Rect;
+use std::any::Any;
+use std::cell::Cell;
+use std::cell::RefCell;
 use std::mem;
 use termion::cursor;
 use termion::event::Event;
 use termion::event::Key;
+use termion::event::MouseButton;
+use termion::event::MouseEvent;
 
 pub struct UI {
     current_view: Box<dyn FullView>,
     view_stack: Vec<Box<dyn FullView>>,
     input_mode: InputMode,
+    /// A dialog drawn over the current view, which takes all input until it is answered.
+    dialog: Option<Box<dyn dialog::Dialog>>,
+    /// The index of the notice last drawn as a toast, if one was.
+    toast: Cell<Option<usize>>,
+    /// The input mode to go back to when a dialog the UI opened itself, rather than the view,
+    /// closes.
+    overlay: Option<InputMode>,
+    /// The region of the view the mouse was pressed on, until it is let go.
+    pressed: Option<usize>,
+    /// A transition held back until the views it closes let go, see `FullView::can_leave`.
+    held: Option<Transition>,
 }
 
 pub trait FullView {
     fn full_redraw(&self);
     fn update(&mut self, input: Input) -> Option<Transition>;
-    fn restart(&mut self, _: Box<dyn FullView>) -> Option<Transition> {
+    /// What the view is called in the breadcrumbs.
+    fn title(&self) -> String;
+    /// What the player can do in `mode`, for the help.
+    fn actions(&self, mode: InputMode) -> Vec<Action>;
+    /// Called when the views above this one are popped, with what the last of them returned.
+    fn restart(&mut self, _returned: Option<Box<dyn Any>>) -> Option<Transition> {
         self.full_redraw();
         None
     }
@@ -23,8 +50,75 @@ pub trait FullView {
         self.full_redraw();
         None
     }
+    /// The clickable parts of what was last drawn. Later regions are on top of earlier ones.
+    fn hit_regions(&self) -> Vec<HitRegion> {
+        Vec::new()
+    }
+    /// Called when the region with `target` is clicked.
+    fn click(&mut self, _target: usize) -> Option<Transition> {
+        None
+    }
+    /// Called when the mouse is pressed on the region with `from` and let go on the one with `to`,
+    /// after `click` has been called for `from`.
+    fn drag(&mut self, _from: usize, _to: usize) -> Option<Transition> {
+        None
+    }
+    /// Called with the answer to a dialog the view opened, after the dialog has closed.
+    fn dialog_result(&mut self, _result: dialog::DialogResult) -> Option<Transition> {
+        None
+    }
+    /// The smallest terminal, as `(width, height)`, the view can be drawn in. In anything smaller
+    /// a notice is shown instead.
+    fn min_size(&self) -> (u16, u16) {
+        (30, 8)
+    }
+    /// Marks the view as somewhere `Transition::PopTo` can return to.
+    fn marker(&self) -> Option<Marker> {
+        None
+    }
+    /// The commands the palette offers while the view is current, each run with `run_command`.
+    fn commands(&self) -> Vec<palette::Command> {
+        Vec::new()
+    }
+    /// Runs the command that `commands` gave `id`, with the number typed after it, if any.
+    fn run_command(&mut self, _id: usize, _arg: Option<u32>) -> palette::Ran {
+        Ok(None)
+    }
+    /// Called before the view is popped or dropped. Giving a transition, such as a dialog asking
+    /// about unsaved changes, holds back the one that would close the view until the view makes
+    /// `Transition::Proceed`.
+    fn can_leave(&mut self) -> Option<Transition> {
+        None
+    }
+}
+
+/// An input and what it does, as listed in the help.
+pub type Action = (Input, &'static str);
+
+/// Builds a view when it is opened, so that each visit starts afresh.
+pub type Factory = fn() -> Box<dyn FullView>;
+
+/// Views that can be popped back to, wherever they are in the stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Marker {
+    Rockets,
+}
+
+/// A clickable part of the screen.
+#[derive(Clone, Copy, Debug)]
+pub struct HitRegion {
+    pub area: Rect,
+    pub target: usize,
 }
 
+/// Records the hit regions of a view as it draws, tagging each with what it selects.
+///
+/// The `target` of each region it hands out is an index that `get` turns back into the tag.
+pub struct HitMap<T> {
+    regions: RefCell<Vec<(HitRegion, T)>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Input {
     Up,
     Down,
@@ -35,1133 +129,5300 @@ pub enum Input {
     Type(char),
     Del,
     BkSpace,
+    /// Moves back to the start of a word, while typing.
+    WordLeft,
+    /// Moves forward to the end of a word, while typing.
+    WordRight,
+    /// Deletes back to the start of a word, while typing.
+    WordBkSpace,
+    /// Deletes forward to the end of a word, while typing.
+    WordDel,
+    /// Marks where a range of items starts, to act on them together.
+    Mark,
+    Cut,
+    Copy,
+    Paste,
+    Duplicate,
+    /// Moves the selected items one place back.
+    MoveLeft,
+    /// Moves the selected items one place forward.
+    MoveRight,
+    /// Adds the next items before the selected one.
+    InsertBefore,
+    /// Adds the next items after the selected one.
+    InsertAfter,
+    Undo,
+    Redo,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Search,
+    /// Shows or hides the decimal value next to sexagesimal numbers.
+    Details,
+    /// Lists what each key does in the current view.
+    Help,
+    /// Opens the command palette.
+    Palette,
+    Key(Key),
 }
 
 pub enum Transition {
     Push(Box<dyn FullView>),
+    /// Swaps the current view for another, which pops back to the same view it would have.
+    Replace(Box<dyn FullView>),
     Pop,
+    /// Pops this many views, ending the game if that is all of them.
+    PopN(usize),
+    /// Pops to the nearest view below with the marker, or does nothing if there is none.
+    PopTo(Marker),
+    /// Pops the current view, handing the value to the one below through `restart`.
+    Return(Box<dyn Any>),
+    /// Drops every view, starting again from this one.
+    ResetTo(Box<dyn FullView>),
+    /// Opens a dialog over the current view, which gets the answer through `dialog_result`.
+    Dialog(Box<dyn dialog::Dialog>),
     InputMode(InputMode),
     Multiple(Vec<Transition>),
+    /// Makes the transition held back by `FullView::can_leave`, once the view has let go.
+    Proceed,
 }
 
 type Continue = bool;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum InputMode {
     Control,
     Type,
+    /// Passes every key through as `Input::Key`, for views that need the raw key.
+    Capture,
+}
+
+impl<T: Copy> HitMap<T> {
+    pub fn new() -> HitMap<T> {
+        HitMap {
+            regions: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Forgets all regions, to be called at the start of a redraw.
+    pub fn clear(&self) {
+        self.regions.borrow_mut().clear();
+    }
+
+    pub fn add(&self, area: Rect, tag: T) {
+        let mut regions = self.regions.borrow_mut();
+        let target = regions.len();
+        regions.push((HitRegion { area, target }, tag));
+    }
+
+    pub fn regions(&self) -> Vec<HitRegion> {
+        self.regions.borrow().iter().map(|(r, _)| *r).collect()
+    }
+
+    pub fn get(&self, target: usize) -> Option<T> {
+        self.regions.borrow().get(target).map(|(_, t)| *t)
+    }
 }
 
 impl UI {
     pub fn new() -> UI {
         UI {
-            current_view: Box::new(basic_tl_view::View::new()),
-            view_stack: vec![Box::new(exit_confirmation_view::View::new())],
+            current_view: UI::home(),
+            view_stack: Vec::new(),
             input_mode: InputMode::Control,
+            dialog: None,
+            toast: Cell::new(None),
+            overlay: None,
+            pressed: None,
+            held: None,
         }
     }
 
+    /// The main menu, which every other view is opened from.
+    fn home() -> Box<dyn FullView> {
+        Box::new(basic_tl_view::View::new())
+    }
+
     pub fn start(&mut self) {
         ui_print!("{}", cursor::Hide);
-        self.current_view.full_redraw();
+        self.redraw();
+    }
+
+    /// Redraws the current view at the current terminal size, or the too small notice if it does
+    /// not fit.
+    pub fn redraw(&self) {
+        if self.fits() {
+            self.current_view.full_redraw();
+            self.draw_header();
+            if let Some(dialog) = &self.dialog {
+                let (width, height) = dialog.size();
+                dialog.draw(Rect::screen().centered(width, height));
+            }
+            self.draw_toast();
+        } else {
+            self.draw_too_small();
+        }
+    }
+
+    /// Shows everything drawn since the last call on the terminal.
+    pub fn present(&self) {
+        FRAME.lock().unwrap().present();
     }
 
     pub fn input(&mut self, event: &Event) -> Continue {
-        if let Some(input) = self.input_mode.map(event) {
-            let trans = self.current_view.update(input);
-            self.handle_trans(trans)
+        if let Event::Mouse(MouseEvent::Press(MouseButton::Left, x, y)) = *event {
+            let cont = self.click(x, y);
+            self.check_fits(cont);
+            cont
+        } else if let Event::Mouse(MouseEvent::Release(x, y)) = *event {
+            let cont = self.release(x, y);
+            self.check_fits(cont);
+            cont
+        } else if let Some(input) = self.input_mode.map(event) {
+            self.update(input)
         } else {
             true
         }
     }
 
+    /// Hands `input` to the current view, as if it had been mapped from a key.
+    pub fn update(&mut self, input: Input) -> Continue {
+        if input == Input::Details {
+            let mut settings = SETTINGS.lock().unwrap();
+            settings.show_decimal = !settings.show_decimal;
+            drop(settings);
+            self.redraw();
+            return true;
+        }
+        if input == Input::Help && self.dialog.is_none() {
+            self.open_help();
+            self.check_fits(true);
+            return true;
+        }
+        if input == Input::Palette && self.dialog.is_none() {
+            self.open_palette();
+            self.check_fits(true);
+            return true;
+        }
+        if let Some(dialog) = &mut self.dialog {
+            let cont = match dialog.update(input) {
+                Some(result) => self.close_dialog(result),
+                None => {
+                    self.redraw();
+                    true
+                }
+            };
+            self.check_fits(cont);
+            return cont;
+        }
+        let trans = self.current_view.update(input);
+        let cont = self.handle_trans(trans);
+        self.check_fits(cont);
+        cont
+    }
+
+    /// Views redraw themselves as they handle input, without checking the size or drawing the
+    /// header.
+    fn check_fits(&self, cont: Continue) {
+        if !cont {
+            return;
+        }
+        if self.fits() {
+            self.draw_header();
+            self.draw_toast();
+        } else {
+            self.draw_too_small();
+        }
+    }
+
+    fn fits(&self) -> bool {
+        let screen = Rect::screen();
+        let (width, height) = self.min_size();
+        screen.width >= width && screen.height >= height
+    }
+
+    /// The smallest terminal the current view, its header and any dialog over it can be drawn in.
+    fn min_size(&self) -> (u16, u16) {
+        let (width, height) = self.current_view.min_size();
+        let height = height + 1;
+        match &self.dialog {
+            Some(dialog) => {
+                let (dialog_width, dialog_height) = dialog.size();
+                (width.max(dialog_width), height.max(dialog_height))
+            }
+            None => (width, height),
+        }
+    }
+
+    /// The title of each open view that fits in the header, with how deep in the stack the view
+    /// is and where its title is drawn.
+    ///
+    /// The first titles are left off when there is not enough room, and a separator is drawn in
+    /// front to show that they are missing.
+    fn breadcrumbs(&self) -> Vec<(usize, String, Rect)> {
+        let titles: Vec<String> = self
+            .view_stack
+            .iter()
+            .chain(Some(&self.current_view))
+            .map(|view| view.title())
+            .collect();
+        let separator = font_metrics::width(&crumb_separator());
+        let line = Rect::screen().line(0);
+
+        let mut first = titles.len() - 1;
+        let mut width = font_metrics::width(&titles[first]);
+        while first > 0 {
+            let wider = width + separator + font_metrics::width(&titles[first - 1]);
+            let elided = if first > 1 { separator } else { 0 };
+            if wider + elided > line.width {
+                break;
+            }
+            width = wider;
+            first -= 1;
+        }
+
+        let mut x = line.x;
+        let mut crumbs = Vec::new();
+        for (depth, title) in titles.into_iter().enumerate().skip(first) {
+            if depth > 0 {
+                x += separator;
+            }
+            let width = font_metrics::width(&title);
+            crumbs.push((depth, title, Rect::new(x, line.y, width, 1)));
+            x += width;
+        }
+        crumbs
+    }
+
+    /// Draws the breadcrumbs on the line above the view.
+    fn draw_header(&self) {
+        use widget::{Label, Widget};
+
+        let line = Rect::screen().line(0);
+        ui_print!(
+            "{}{}",
+            cursor::Goto(line.x, line.y),
+            termion::clear::CurrentLine
+        );
+        let separator = crumb_separator();
+        let separator_width = font_metrics::width(&separator);
+        for (depth, title, area) in self.breadcrumbs() {
+            if depth > 0 {
+                let x = area.x - separator_width;
+                Label::new(separator.as_str()).draw(Rect::new(x, area.y, separator_width, 1));
+            }
+            Label::new(title).draw(area);
+        }
+    }
+
+    /// Whether a toast has been posted or has run out since the screen was last drawn.
+    pub fn toast_changed(&self) -> bool {
+        GAME.news.toast().map(|(idx, _)| idx) != self.toast.get()
+    }
+
+    /// Draws the latest notice over the bottom line, while it is new.
+    fn draw_toast(&self) {
+        use widget::{Label, Widget};
+
+        let toast = GAME.news.toast();
+        self.toast.set(toast.as_ref().map(|(idx, _)| *idx));
+        if let Some((_, notice)) = toast {
+            let screen = Rect::screen();
+            let line = screen.line(screen.height - 1);
+            ui_print!(
+                "{}{}{}",
+                cursor::Goto(line.x, line.y),
+                termion::clear::CurrentLine,
+                termion::style::Invert
+            );
+            Label::new(format!(" {}: {} ", notice.topic.name(), notice.text)).draw(line);
+            ui_print!("{}", termion::style::NoInvert);
+        }
+    }
+
+    fn draw_too_small(&self) {
+        use widget::{Label, Widget};
+
+        let (width, height) = self.min_size();
+        let area = Rect::screen();
+        ui_print!("{}", termion::clear::All);
+        Label::new("Terminal too small").draw(area.line(0));
+        Label::new(format!("Need {}x{}", width, height)).draw(area.line(1));
+        Label::new(format!("Have {}x{}", area.width, area.height)).draw(area.line(2));
+    }
+
+    fn click(&mut self, x: u16, y: u16) -> Continue {
+        self.pressed = None;
+        if self.input_mode == InputMode::Capture || !self.fits() {
+            return true;
+        }
+        if let Some(dialog) = &mut self.dialog {
+            let target = dialog
+                .hit_regions()
+                .iter()
+                .rev()
+                .find(|r| r.area.contains(x, y))
+                .map(|r| r.target);
+            return match target.and_then(|target| dialog.click(target)) {
+                Some(result) => self.close_dialog(result),
+                None => {
+                    self.redraw();
+                    true
+                }
+            };
+        }
+        let depth = self.view_stack.len();
+        let crumb = self
+            .breadcrumbs()
+            .into_iter()
+            .find(|(_, _, area)| area.contains(x, y));
+        match crumb {
+            //Clicking a crumb goes back to that view, and the first starts again from the menu
+            Some((0, _, _)) => return self.handle_trans(Some(Transition::ResetTo(UI::home()))),
+            Some((clicked, _, _)) => {
+                return self.handle_trans(Some(Transition::PopN(depth - clicked)))
+            }
+            None => {}
+        }
+        self.pressed = self.target_at(x, y);
+        match self.pressed {
+            Some(target) => {
+                let trans = self.current_view.click(target);
+                self.handle_trans(trans)
+            }
+            None => true,
+        }
+    }
+
+    /// Ends a press on the view, dragging from where it was pressed if it is let go on another
+    /// region.
+    fn release(&mut self, x: u16, y: u16) -> Continue {
+        let from = match self.pressed.take() {
+            Some(from) => from,
+            None => return true,
+        };
+        if self.dialog.is_some() || !self.fits() {
+            return true;
+        }
+        match self.target_at(x, y) {
+            Some(to) if to != from => {
+                let trans = self.current_view.drag(from, to);
+                self.handle_trans(trans)
+            }
+            _ => true,
+        }
+    }
+
+    /// The topmost region of the current view at `x`, `y`.
+    fn target_at(&self, x: u16, y: u16) -> Option<usize> {
+        self.current_view
+            .hit_regions()
+            .iter()
+            .rev()
+            .find(|r| r.area.contains(x, y))
+            .map(|r| r.target)
+    }
+
     fn handle_trans(&mut self, transition: Option<Transition>) -> Continue {
+        if let Some(transition) = transition {
+            if let Some((depth, asked)) = self.ask_to_leave(&transition) {
+                self.held = Some(match transition {
+                    //The views above the one that asked are already popped
+                    Transition::PopN(count) => Transition::PopN(count - depth),
+                    transition => transition,
+                });
+                return self.handle_trans(Some(asked));
+            }
+            return self.make_trans(transition);
+        }
+        true
+    }
+
+    /// Makes a transition the views it closes have let go of.
+    fn make_trans(&mut self, transition: Transition) -> Continue {
         match transition {
-            Some(Transition::Push(mut v)) => {
-                mem::swap(&mut v, &mut self.current_view);
-                self.view_stack.push(v);
-                let start_trans = self.current_view.start();
-                if !self.handle_trans(start_trans) {
-                    return false;
-                }
+            Transition::Push(v) => {
+                let last = mem::replace(&mut self.current_view, v);
+                self.view_stack.push(last);
+                return self.start_view();
             }
-            Some(Transition::Pop) => {
-                if let Some(mut v) = self.view_stack.pop() {
-                    mem::swap(&mut v, &mut self.current_view);
-                    self.current_view.restart(v);
-                    self.current_view.full_redraw();
-                } else {
-                    return false;
+            Transition::Replace(v) => {
+                self.current_view = v;
+                return self.start_view();
+            }
+            Transition::Pop => return self.pop(1, None),
+            Transition::PopN(count) => return self.pop(count, None),
+            Transition::PopTo(marker) => {
+                let below = self
+                    .view_stack
+                    .iter()
+                    .rev()
+                    .position(|v| v.marker() == Some(marker));
+                if let Some(below) = below {
+                    return self.pop(below + 1, None);
                 }
             }
-            Some(Transition::InputMode(mode)) => {
+            Transition::Return(value) => return self.pop(1, Some(value)),
+            Transition::ResetTo(v) => {
+                self.view_stack.clear();
+                self.current_view = v;
+                return self.start_view();
+            }
+            Transition::InputMode(mode) => {
                 self.input_mode = mode;
             }
-            Some(Transition::Multiple(vec)) => {
+            Transition::Dialog(dialog) => {
+                self.input_mode = dialog.input_mode();
+                self.dialog = Some(dialog);
+                self.redraw();
+            }
+            Transition::Multiple(vec) => {
                 let mut ret = true;
                 for transition in vec {
-                    ret &= self.handle_trans(Some(transition));
+                    ret &= self.make_trans(transition);
                 }
                 if !ret {
                     return false;
                 }
             }
-            None => {}
+            Transition::Proceed => {
+                if let Some(held) = self.held.take() {
+                    return self.handle_trans(Some(held));
+                }
+            }
+        }
+        true
+    }
+
+    /// How many views `transition` closes, counting the current one. For several transitions it
+    /// is the first to close any that counts.
+    fn closes(&self, transition: &Transition) -> usize {
+        let open = self.view_stack.len() + 1;
+        match transition {
+            Transition::Replace(_) | Transition::Pop | Transition::Return(_) => 1,
+            Transition::PopN(count) => (*count).min(open),
+            Transition::PopTo(marker) => self
+                .view_stack
+                .iter()
+                .rev()
+                .position(|v| v.marker() == Some(*marker))
+                .map_or(0, |below| below + 1),
+            Transition::ResetTo(_) => open,
+            Transition::Multiple(vec) => vec
+                .iter()
+                .map(|transition| self.closes(transition))
+                .find(|&count| count > 0)
+                .unwrap_or(0),
+            _ => 0,
         }
+    }
+
+    /// Asks each view `transition` would close, from the current one down, whether it can be
+    /// left. The first that can not becomes current, popping those above it, and how deep it was
+    /// is returned with what it gives instead.
+    fn ask_to_leave(&mut self, transition: &Transition) -> Option<(usize, Transition)> {
+        for depth in 0..self.closes(transition) {
+            let view = match depth {
+                0 => &mut self.current_view,
+                _ => {
+                    let below = self.view_stack.len() - depth;
+                    &mut self.view_stack[below]
+                }
+            };
+            if let Some(asked) = view.can_leave() {
+                self.pop(depth, None);
+                return Some((depth, asked));
+            }
+        }
+        None
+    }
+
+    /// Starts a view that has just become current.
+    fn start_view(&mut self) -> Continue {
+        self.input_mode = InputMode::Control;
+        let trans = self.current_view.start();
+        self.handle_trans(trans)
+    }
+
+    /// Pops `count` views, handing `returned` to the one that becomes current, or ends the game if
+    /// that leaves none.
+    fn pop(&mut self, count: usize, returned: Option<Box<dyn Any>>) -> Continue {
+        if count == 0 {
+            return true;
+        }
+        if count > self.view_stack.len() {
+            return false;
+        }
+        self.view_stack.truncate(self.view_stack.len() - count + 1);
+        self.current_view = self.view_stack.pop().unwrap();
+        self.input_mode = InputMode::Control;
+        let trans = self.current_view.restart(returned);
+        if !self.handle_trans(trans) {
+            return false;
+        }
+        self.redraw();
         true
     }
+
+    /// Opens the help over the current view, listing the actions for the input mode with the keys
+    /// bound to them.
+    fn open_help(&mut self) {
+        let mode = self.input_mode;
+        let mut actions = self.current_view.actions(mode);
+        if mode == InputMode::Control {
+            actions.push((Input::Details, "Show decimal values"));
+        }
+        actions.push((Input::Help, "Show this help"));
+        actions.push((Input::Palette, "Open the command palette"));
+        if SETTINGS.lock().unwrap().debug_overlay {
+            actions.push((Input::Key(Key::Alt('n')), "Scroll the debug panel down"));
+            actions.push((Input::Key(Key::Alt('p')), "Scroll the debug panel up"));
+        }
+
+        let keymap = KEYMAP.lock().unwrap();
+        let lines = actions
+            .into_iter()
+            .filter_map(|(input, description)| {
+                let keys = match input {
+                    Input::Type(c) => vec![Key::Char(c)],
+                    Input::Key(key) => vec![key],
+                    input => keymap.keys_for(mode, input),
+                };
+                //Actions the player has unbound can not be done, so are left out
+                if keys.is_empty() {
+                    return None;
+                }
+                let keys: Vec<String> = keys.into_iter().map(keymap::key_name).collect();
+                Some((keys.join(", "), description))
+            })
+            .collect();
+        drop(keymap);
+
+        let title = match mode {
+            InputMode::Control | InputMode::Capture => self.current_view.title(),
+            InputMode::Type => format!("{} (typing)", self.current_view.title()),
+        };
+        self.open_overlay(Box::new(dialog::Help::new(title, lines)));
+    }
+
+    /// Opens the command palette over the current view.
+    fn open_palette(&mut self) {
+        let commands = self.commands();
+        self.open_overlay(Box::new(palette::Palette::new(commands)));
+    }
+
+    /// The name and argument of every command that can be run, those registered for anywhere
+    /// first and then the current view's.
+    fn commands(&self) -> Vec<(String, Option<&'static str>)> {
+        let registered = palette::COMMANDS.lock().unwrap();
+        registered
+            .iter()
+            .map(|command| (command.name.clone(), command.arg))
+            .chain(
+                self.current_view
+                    .commands()
+                    .into_iter()
+                    .map(|command| (command.name, command.arg)),
+            )
+            .collect()
+    }
+
+    /// Runs the command at `idx` in `commands`, telling the player if it fails.
+    fn run_command(&mut self, idx: usize, arg: Option<u32>) -> Continue {
+        let registered = palette::COMMANDS.lock().unwrap().len();
+        let ran = if idx < registered {
+            let run = match &palette::COMMANDS.lock().unwrap()[idx].run {
+                palette::Run::Anywhere(run) => Some(run.clone()),
+                palette::Run::InView(_) => None,
+            };
+            //The lock is let go first, so commands are free to use the palette
+            match run {
+                Some(run) => run(arg),
+                None => Ok(None),
+            }
+        } else {
+            match self.current_view.commands().get(idx - registered) {
+                Some(palette::Command {
+                    run: palette::Run::InView(id),
+                    ..
+                }) => self.current_view.run_command(*id, arg),
+                _ => Ok(None),
+            }
+        };
+        match ran {
+            Ok(trans) => self.handle_trans(trans),
+            Err(message) => {
+                self.open_overlay(Box::new(dialog::Alert::new(message)));
+                true
+            }
+        }
+    }
+
+    /// Opens a dialog that the UI answers itself, rather than the view.
+    fn open_overlay(&mut self, dialog: Box<dyn dialog::Dialog>) {
+        self.overlay = Some(self.input_mode);
+        self.handle_trans(Some(Transition::Dialog(dialog)));
+    }
+
+    /// Closes the dialog and hands `result` to the view that opened it.
+    fn close_dialog(&mut self, result: dialog::DialogResult) -> Continue {
+        self.dialog = None;
+        if let Some(mode) = self.overlay.take() {
+            self.input_mode = mode;
+            let cont = match result {
+                dialog::DialogResult::Command(Some((idx, arg))) => self.run_command(idx, arg),
+                _ => true,
+            };
+            if cont {
+                self.redraw();
+            }
+            return cont;
+        }
+        self.input_mode = InputMode::Control;
+        let trans = self.current_view.dialog_result(result);
+        //Whatever was held back waits on the answer, and is dropped unless the view goes on
+        if !matches!(trans, Some(Transition::Proceed)) {
+            self.held = None;
+        }
+        let cont = self.handle_trans(trans);
+        if cont {
+            self.redraw();
+        }
+        cont
+    }
+}
+
+fn crumb_separator() -> String {
+    format!(" {} ", glyphs::CRUMB_SEPARATOR.get())
 }
 
 impl Drop for UI {
     fn drop(&mut self) {
-        print!(
+        ui_print!(
             "{}{}{}",
             termion::clear::All,
             termion::cursor::Goto(1, 1),
             termion::cursor::Show
         );
-        stdout().flush().unwrap();
+        self.present();
     }
 }
 
 impl InputMode {
     fn map(&self, event: &Event) -> Option<Input> {
+        let key = match event {
+            Event::Key(k) => *k,
+            Event::Mouse(MouseEvent::Press(MouseButton::WheelUp, _, _)) => {
+                return self.scroll(Input::Up)
+            }
+            Event::Mouse(MouseEvent::Press(MouseButton::WheelDown, _, _)) => {
+                return self.scroll(Input::Down)
+            }
+            Event::Mouse(_) => return None,
+            Event::Unsupported(_) => return None,
+        };
+        if let Some(input) = KEYMAP.lock().unwrap().get(*self, key) {
+            return Some(input);
+        }
+        match (self, key) {
+            (InputMode::Type, Key::Char(c)) => Some(Input::Type(c)),
+            (InputMode::Capture, k) => Some(Input::Key(k)),
+            _ => None,
+        }
+    }
+
+    /// The mouse wheel moves the selection, which is what scrolls lists.
+    fn scroll(&self, input: Input) -> Option<Input> {
         match self {
-            InputMode::Control => match event {
-                Event::Key(k) => match k {
-                    Key::Left | Key::Char('a') => Some(Input::Left),
-                    Key::Right | Key::Char('d') => Some(Input::Right),
-                    Key::Up | Key::Char('w') => Some(Input::Up),
-                    Key::Down | Key::Char('s') => Some(Input::Down),
-                    Key::Char('\n') | Key::Char(' ') => Some(Input::Select),
-                    Key::Esc => Some(Input::Back),
-                    Key::Delete | Key::Backspace => Some(Input::Del),
-                    _ => None,
-                },
-                Event::Mouse(_) => None,
-                Event::Unsupported(_) => None,
-            },
-            InputMode::Type => match event {
-                Event::Key(Key::Left) | Event::Key(Key::Ctrl('f')) => Some(Input::Left),
-                Event::Key(Key::Right) | Event::Key(Key::Ctrl('b')) => Some(Input::Right),
-                Event::Key(Key::Up) | Event::Key(Key::Ctrl('p')) => Some(Input::Up),
-                Event::Key(Key::Down) | Event::Key(Key::Ctrl('n')) => Some(Input::Down),
-                Event::Key(Key::Delete) | Event::Key(Key::Ctrl('d')) => Some(Input::Del),
-                Event::Key(Key::Backspace) => Some(Input::BkSpace),
-                Event::Key(Key::Char(c)) => Some(Input::Type(*c)),
-                Event::Key(Key::Esc) => Some(Input::Back),
-                _ => None,
-            },
+            InputMode::Control => Some(input),
+            InputMode::Type | InputMode::Capture => None,
         }
     }
 }
 
 mod view_prelude {
+    pub use super::dialog::{Alert, Choice, Confirm, DialogResult, Prompt};
+    pub use super::layout::{Rect, Size};
+    pub use super::palette::{Command, Ran};
+    pub use super::scroll_list::{ScrollList, Taken};
     pub use super::type_box::TypeBox;
+    pub use super::widget::{Button, Label, List, RocketDiagram, Widget};
+    pub use super::Action;
+    pub use super::Factory;
     pub use super::FullView;
+    pub use super::HitMap;
+    pub use super::HitRegion;
     pub use super::Input;
     pub use super::InputMode;
+    pub use super::Marker;
     pub use super::Transition;
+    pub use crate::settings::SETTINGS;
+    pub use crate::ui_print;
     pub use crate::GAME;
 }
 
-pub mod type_box {
-    use super::Input;
+pub mod layout {
+    use crate::frame::FRAME;
 
-    pub struct TypeBox {
-        pub content: String,
-        cursor: u8,
-        left_scroll: u8,
-        len: u8,
-        loc_x: u16,
-        loc_y: u16,
-        active: bool,
+    /// A rectangle of the screen, in one-based terminal coordinates.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Rect {
+        pub x: u16,
+        pub y: u16,
+        pub width: u16,
+        pub height: u16,
     }
 
-    type ShouldRedraw = bool;
+    /// How much of a row or column split a part gets.
+    #[derive(Clone, Copy, Debug)]
+    pub enum Size {
+        /// Exactly this many cells, or whatever is left if there is not enough room.
+        Fixed(u16),
+        /// An equal share of what the fixed parts leave over.
+        Fill,
+    }
 
-    impl TypeBox {
-        pub fn activate(&mut self, activate: bool) {
-            if self.active && !activate {
-                print!("{}{}", termion::cursor::Hide, termion::cursor::Restore);
+    impl Rect {
+        pub fn new(x: u16, y: u16, width: u16, height: u16) -> Rect {
+            Rect {
+                x,
+                y,
+                width,
+                height,
             }
+        }
 
-            if !self.active && activate {
-                print!("{}", termion::cursor::BlinkingBlock)
-            }
-            self.active = activate;
+        /// The whole terminal.
+        pub fn screen() -> Rect {
+            let (width, height) = FRAME.lock().unwrap().size();
+            Rect::new(1, 1, width, height)
         }
 
-        pub fn take_input(&mut self, input: &Input) -> ShouldRedraw {
-            if !self.active {
-                return false;
-            }
-            match input {
-                Input::Type(c) if *c != '\n' => {
-                    if self.cursor == self.len {
-                        self.left_scroll += 1;
-                    } else {
-                        self.cursor += 1;
-                    }
-                    self.content
-                        .insert((self.cursor + self.left_scroll) as usize, *c);
-                    true
-                }
-                Input::Left => {
-                    if self.cursor != 0 {
-                        self.cursor -= 1;
-                    }
-                    true
-                }
-                Input::Right => {
-                    if self.cursor != self.len - 1 {
-                        self.cursor += 1;
-                    }
-                    true
-                }
-                Input::BkSpace => {
-                    if self.cursor != 0 {
-                        self.cursor -= 1;
-                        self.content.remove(self.cursor as usize);
-                    }
-                    true
-                }
-                Input::Del => {
-                    if (self.cursor as usize) < self.content.len() {
-                        self.content.remove(self.cursor as usize);
-                    }
-                    true
-                }
-                _ => false,
-            }
+        /// The part of the screen views are drawn in, below the breadcrumbs.
+        pub fn view() -> Rect {
+            let screen = Rect::screen();
+            Rect::new(1, 2, screen.width, screen.height.saturating_sub(1))
         }
 
-        pub fn before_render(&self) {
-            if self.active {
-                print!("{}", termion::cursor::Hide);
-            }
+        pub fn contains(&self, x: u16, y: u16) -> bool {
+            x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
         }
 
-        pub fn draw(&self) {
-            use termion::style;
-            if self.active {
-                print!("{}", style::Invert);
-            }
-            print!(
-                "{}{}{}",
-                termion::cursor::Goto(self.loc_x, self.loc_y),
-                self.content,
-                " ".repeat(self.len as usize - self.content.len())
-            );
-            if self.active {
-                print!("{}{}", color::Fg(color::Reset), color::Bg(color::Reset));
-            }
+        pub fn is_empty(&self) -> bool {
+            self.width == 0 || self.height == 0
         }
 
-        pub fn after_render(&self) {
-            if self.active {
-                print!(
-                    "{}{}",
-                    termion::cursor::Goto(self.loc_x + self.cursor as u16, self.loc_y),
-                    termion::cursor::Show
-                );
-            }
+        /// Splits into parts stacked top to bottom.
+        pub fn rows(self, sizes: &[Size]) -> Vec<Rect> {
+            split(self.height, sizes)
+                .into_iter()
+                .map(|(offset, height)| Rect::new(self.x, self.y + offset, self.width, height))
+                .collect()
         }
 
-        pub fn new() -> TypeBox {
-            TypeBox {
-                content: String::new(),
-                cursor: 0,
-                len: 0,
-                loc_x: 1,
-                loc_y: 1,
-                active: false,
-            }
+        /// Splits into parts placed left to right.
+        pub fn columns(self, sizes: &[Size]) -> Vec<Rect> {
+            split(self.width, sizes)
+                .into_iter()
+                .map(|(offset, width)| Rect::new(self.x + offset, self.y, width, self.height))
+                .collect()
         }
 
-        pub fn at(self, loc_x: u16, loc_y: u16) -> TypeBox {
-            assert!(loc_x >= 1);
-            assert!(loc_y >= 1);
-            TypeBox {
-                loc_x,
-                loc_y,
-                ..self
+        /// The `idx`th line, which is empty if it is past the bottom.
+        pub fn line(self, idx: u16) -> Rect {
+            if idx >= self.height {
+                Rect::new(self.x, self.y + self.height, self.width, 0)
+            } else {
+                Rect::new(self.x, self.y + idx, self.width, 1)
             }
         }
 
-        pub fn with_len(self, len: u8) -> TypeBox {
-            TypeBox { len, ..self }
+        /// Shrinks by `margin` on every side.
+        pub fn inset(self, margin: u16) -> Rect {
+            let width = self.width.saturating_sub(2 * margin);
+            let height = self.height.saturating_sub(2 * margin);
+            Rect::new(self.x + margin, self.y + margin, width, height)
+        }
+
+        /// Moves the left edge right by `columns`.
+        pub fn indent(self, columns: u16) -> Rect {
+            let columns = columns.min(self.width);
+            Rect::new(self.x + columns, self.y, self.width - columns, self.height)
+        }
+
+        /// A rectangle of at most the given size, centered in this one.
+        pub fn centered(self, width: u16, height: u16) -> Rect {
+            let width = width.min(self.width);
+            let height = height.min(self.height);
+            Rect::new(
+                self.x + (self.width - width) / 2,
+                self.y + (self.height - height) / 2,
+                width,
+                height,
+            )
         }
     }
-}
 
-mod basic_tl_view {
-    use super::view_prelude::*;
-    use crate::ui_print;
-    use std::convert::TryInto;
-    use std::io::stdout;
-    use std::io::Write;
-    use termion::{clear, cursor};
+    /// Splits `total` cells into `(offset, len)` pairs according to `sizes`.
+    fn split(total: u16, sizes: &[Size]) -> Vec<(u16, u16)> {
+        let fixed: u16 = sizes
+            .iter()
+            .map(|s| match s {
+                Size::Fixed(n) => *n,
+                Size::Fill => 0,
+            })
+            .sum();
+        let fills = sizes.iter().filter(|s| matches!(s, Size::Fill)).count() as u16;
+        let mut spare = total.saturating_sub(fixed);
 
-    pub struct View {
-        title: &'static str,
-        selection: u8,
-        tabs: Vec<Tab>,
+        let mut offset = 0;
+        let mut fills_left = fills;
+        sizes
+            .iter()
+            .map(|size| {
+                let len = match size {
+                    Size::Fixed(n) => (*n).min(total - offset),
+                    Size::Fill => {
+                        let len = spare / fills_left;
+                        spare -= len;
+                        fills_left -= 1;
+                        len
+                    }
+                };
+                let part = (offset, len);
+                offset += len;
+                part
+            })
+            .collect()
     }
+}
 
-    struct Tab {
-        name: &'static str,
-        transition: Option<Transition>,
+/// Retained widgets that draw themselves into a `Rect` handed to them by the view's layout.
+pub mod widget {
+    use super::layout::Rect;
+    use crate::diagram;
+    use crate::font_metrics;
+    use crate::glyphs;
+    use crate::rocket::Rocket;
+    use crate::settings::{GlyphMode, SETTINGS};
+    use crate::ui_print;
+    use termion::cursor;
+
+    pub trait Widget {
+        fn draw(&self, area: Rect);
     }
 
-    impl FullView for View {
-        fn full_redraw(&self) {
-            ui_print!("{}{}", clear::All, cursor::Goto(1, 1));
-            ui_print!("{}{}", self.title, cursor::Goto(1, 2));
-            for (idx, tab) in self.tabs.iter().enumerate() {
-                print!("{}", cursor::Goto(1, 2 + idx as u16));
-                if idx as u8 == self.selection {
-                    ui_print!("▶ "); //TODO this is a hack because I need to expand cuneiform_width into font_width
-                } else {
-                    ui_print!("{}", cursor::Right(2));
-                }
-                ui_print!("{}", tab.name);
-            }
-            stdout().flush().unwrap();
+    /// Prints `text` at the top left of `area`, cut off at its right edge.
+    pub fn print_clipped(area: Rect, text: &str) {
+        if area.is_empty() {
+            return;
         }
+        ui_print!(
+            "{}{}",
+            cursor::Goto(area.x, area.y),
+            font_metrics::truncate(text, area.width)
+        );
+    }
 
-        fn update(&mut self, input: Input) -> Option<Transition> {
-            match input {
-                Input::Up => {
-                    if self.selection == 0 {
-                        self.selection = self.max_selection();
-                    } else {
-                        self.selection -= 1;
-                    }
-                    self.full_redraw();
-                    None
-                }
-                Input::Down => {
-                    if self.selection == self.max_selection() {
-                        self.selection = 0;
-                    } else {
-                        self.selection += 1;
-                    }
-                    self.full_redraw();
-                    None
-                }
-                Input::Select => Some(
-                    self.tabs[usize::from(self.selection)]
-                        .transition
-                        .take()
-                        .unwrap(),
-                ),
-                Input::Back => Some(Transition::Pop),
-                _ => None,
-            }
+    /// The marker drawn next to the selected item of a list.
+    pub fn selection_marker() -> &'static str {
+        glyphs::SELECTED.get()
+    }
+
+    pub struct Label {
+        pub text: String,
+    }
+
+    impl Label {
+        pub fn new<S: Into<String>>(text: S) -> Label {
+            Label { text: text.into() }
         }
+    }
 
-        fn restart(&mut self, last: Box<dyn FullView>) -> Option<Transition> {
-            self.tabs[usize::from(self.selection)].transition = Some(Transition::Push(last));
-            self.full_redraw();
-            None
+    impl Widget for Label {
+        fn draw(&self, area: Rect) {
+            print_clipped(area, &self.text);
         }
     }
 
-    impl View {
-        pub fn new() -> View {
-            View {
-                title: "Cuneiforbits",
-                selection: 0,
-                tabs: vec![
-                    Tab {
-                        name: "Missions",
-                        transition: Some(Transition::Push(Box::new(
-                            super::unimplemented_view::View::new(),
-                        ))),
-                    },
-                    Tab {
-                        name: "Jobs",
-                        transition: Some(Transition::Push(Box::new(super::jobs_view::View::new()))),
-                    },
-                    Tab {
-                        name: "Rockets",
-                        transition: Some(Transition::Push(Box::new(
-                            super::rockets_view::View::new(),
-                        ))),
-                    },
-                    Tab {
-                        name: "Tick",
-                        transition: Some(Transition::Push(Box::new(super::tick_view::View::new()))),
-                    },
-                    Tab {
-                        name: "Exit",
-                        transition: Some(Transition::Pop),
-                    },
-                ],
+    /// A button, drawn in brackets when it is focused.
+    pub struct Button {
+        pub label: String,
+        pub focused: bool,
+    }
+
+    impl Button {
+        pub fn new<S: Into<String>>(label: S) -> Button {
+            Button {
+                label: label.into(),
+                focused: false,
             }
         }
 
-        fn max_selection(&self) -> u8 {
-            (self.tabs.len() - 1).try_into().unwrap()
+        pub fn focused(self, focused: bool) -> Button {
+            Button { focused, ..self }
+        }
+
+        /// The number of columns the button takes up.
+        pub fn width(&self) -> u16 {
+            font_metrics::width(&self.label) + 2
         }
     }
-}
 
-mod unimplemented_view {
-    use super::view_prelude::*;
-    use crate::ui_print;
-    use std::io::stdout;
-    use std::io::Write;
-    use termion::{clear, cursor};
+    impl Widget for Button {
+        fn draw(&self, area: Rect) {
+            if self.focused {
+                print_clipped(area, &format!("[{}]", self.label));
+            } else {
+                print_clipped(area, &format!(" {} ", self.label));
+            }
+        }
+    }
 
-    pub struct View;
+    /// A bordered box, which blanks out whatever was drawn under it.
+    pub struct Panel {
+        pub title: String,
+    }
 
-    impl FullView for View {
-        fn full_redraw(&self) {
+    impl Panel {
+        pub fn new<S: Into<String>>(title: S) -> Panel {
+            Panel {
+                title: title.into(),
+            }
+        }
+
+        /// The part of `area` inside the border.
+        pub fn inner(area: Rect) -> Rect {
+            area.inset(1)
+        }
+    }
+
+    impl Widget for Panel {
+        fn draw(&self, area: Rect) {
+            if area.width < 2 || area.height < 2 {
+                return;
+            }
+            let inner_width = (area.width - 2) as usize;
+            let title = font_metrics::truncate(&self.title, area.width - 2);
+            let top = format!(
+                "+{}{}+",
+                title,
+                "-".repeat(inner_width - font_metrics::width(title) as usize)
+            );
+            ui_print!("{}{}", cursor::Goto(area.x, area.y), top);
+            for y in (area.y + 1)..(area.y + area.height - 1) {
+                ui_print!("{}|{}|", cursor::Goto(area.x, y), " ".repeat(inner_width));
+            }
             ui_print!(
-                "n{}{}Unimplemended View. 𒀿|",
-                clear::All,
-                cursor::Goto(1, 1)
+                "{}+{}+",
+                cursor::Goto(area.x, area.y + area.height - 1),
+                "-".repeat(inner_width)
             );
-            eprint!("STDERR");
-            stdout().flush().unwrap();
         }
+    }
 
-        fn update(&mut self, input: Input) -> Option<Transition> {
-            match input {
-                Input::Back => Some(Transition::Pop),
-                _ => None,
+    /// A rocket drawn upright, standing on the bottom of its area, with its top cut off if
+    /// the area is too short.
+    pub struct RocketDiagram {
+        lines: Vec<String>,
+    }
+
+    impl RocketDiagram {
+        pub fn new(rocket: &Rocket) -> RocketDiagram {
+            let ascii = SETTINGS.lock().unwrap().glyphs() == GlyphMode::Ascii;
+            RocketDiagram {
+                lines: diagram::draw(rocket, ascii),
+            }
+        }
+
+        /// The number of columns the diagram takes up.
+        pub fn width(&self) -> u16 {
+            self.lines.first().map_or(0, |l| font_metrics::width(l))
+        }
+    }
+
+    impl Widget for RocketDiagram {
+        fn draw(&self, area: Rect) {
+            let shown = self.lines.len().min(area.height as usize);
+            let top = area.y + area.height - shown as u16;
+            let x = area.x + area.width.saturating_sub(self.width()) / 2;
+            for (idx, line) in self.lines[self.lines.len() - shown..].iter().enumerate() {
+                print_clipped(
+                    Rect::new(x, top + idx as u16, area.width - (x - area.x), 1),
+                    line,
+                );
             }
         }
     }
 
-    impl View {
-        pub fn new() -> View {
-            View
+    /// A vertical list of items of equal height, with a marker beside the selected one.
+    pub struct List {
+        pub selected: Option<usize>,
+        pub item_height: u16,
+    }
+
+    impl List {
+        /// The columns left of each item that hold the selection marker.
+        const MARKER_WIDTH: u16 = 2;
+
+        /// Draws the marker and calls `draw_item` with the area of each of the `len` items that
+        /// fit, returning those areas including the marker column, for hit testing.
+        pub fn draw<F: FnMut(usize, Rect)>(
+            &self,
+            area: Rect,
+            len: usize,
+            mut draw_item: F,
+        ) -> Vec<(usize, Rect)> {
+            let visible = (area.height / self.item_height.max(1)) as usize;
+            (0..len.min(visible))
+                .map(|idx| {
+                    let row = Rect::new(
+                        area.x,
+                        area.y + idx as u16 * self.item_height,
+                        area.width,
+                        self.item_height,
+                    );
+                    if self.selected == Some(idx) {
+                        print_clipped(row, selection_marker());
+                    }
+                    draw_item(idx, row.indent(List::MARKER_WIDTH));
+                    (idx, row)
+                })
+                .collect()
         }
     }
 }
 
-mod jobs_view {
-    use super::view_prelude::*;
+/// A list that scrolls to keep its selection visible, with paging and incremental search.
+pub mod scroll_list {
+    use super::layout::Rect;
+    use super::widget::{print_clipped, selection_marker};
+    use super::{Action, Input, InputMode, Transition};
+    use crate::glyphs;
     use std::cell::Cell;
-    use std::convert::TryFrom;
-    use std::convert::TryInto;
-    use std::io::stdout;
-    use std::io::Write;
-    use termion::{clear, cursor};
 
-    pub struct View {
-        vert_sel: u8,
-        horiz_sel: HorizSel,
-        no_jobs: Cell<bool>,
+    pub struct ScrollList {
+        pub selected: usize,
+        /// Whether the selection marker is shown, for views where focus can leave the list.
+        pub focused: bool,
+        item_height: u16,
+        /// Whether moving past either end wraps around to the other.
+        wrap: bool,
+        /// The first visible item, as of the last draw.
+        offset: Cell<usize>,
+        /// How many items fit, as of the last draw.
+        page: Cell<usize>,
+        search: Option<String>,
     }
 
-    enum HorizSel {
-        Name,
-        Accept,
-        Decline,
+    /// What the list did with an input.
+    pub enum Taken {
+        /// The input was not for the list.
+        Ignored,
+        /// The list changed and the view should redraw.
+        Redraw,
+        /// The list changed and the view should redraw and then make the transition.
+        Transition(Transition),
     }
-    use HorizSel::*;
 
-    impl FullView for View {
-        fn full_redraw(&self) {
-            const MAX_CUSTOMER_NAME_LEN: u16 = 20;
+    impl ScrollList {
+        const MARKER_WIDTH: u16 = 2;
 
-            print!("{}{}", clear::All, cursor::Goto(1, 1));
-            print!("Jobs{}", cursor::Goto(1, 2));
-            let jobs = GAME.available_jobs.lock().unwrap();
-            for (idx, job) in jobs.iter().enumerate() {
-                let row = (3 + idx * 2) as u16;
-                print!(
-                    "{}{}",
-                    cursor::Goto(3, row),
-                    GAME.customers.on(job.customer, |c| c.name.clone()).unwrap()
-                );
-                print!("{}{}", cursor::Goto(5, row + 1), job.payload);
-                print!(
-                    "{}✔{}X",
-                    cursor::Goto(3 + MAX_CUSTOMER_NAME_LEN + 2, row),
-                    cursor::Right(4)
-                );
+        pub fn new(item_height: u16) -> ScrollList {
+            ScrollList {
+                selected: 0,
+                focused: true,
+                item_height,
+                wrap: false,
+                offset: Cell::new(0),
+                page: Cell::new(1),
+                search: None,
             }
-            self.no_jobs.set(jobs.len() == 0);
-            drop(jobs);
-            if !self.no_jobs.get() {
-                let x: u16 = match self.horiz_sel {
-                    Name => 1,
-                    Accept => 3 + MAX_CUSTOMER_NAME_LEN + 1,
-                    Decline => 3 + MAX_CUSTOMER_NAME_LEN + 5,
-                };
-                let symbol = match self.horiz_sel {
-                    Name => "▶".to_string(),
-                    Accept | Decline => format!("[{}]", cursor::Right(2)),
-                };
-                print!(
-                    "{}{}",
-                    cursor::Goto(x, (3 + self.vert_sel * 2).into()),
-                    symbol
-                );
+        }
+
+        pub fn wrapping(self) -> ScrollList {
+            ScrollList { wrap: true, ..self }
+        }
+
+        pub fn is_searching(&self) -> bool {
+            self.search.is_some()
+        }
+
+        /// What the list does with the inputs it takes, for the help.
+        pub fn actions(&self) -> Vec<Action> {
+            if self.is_searching() {
+                return vec![
+                    (Input::Down, "Next match"),
+                    (Input::BkSpace, "Delete a character"),
+                    (Input::Type('\n'), "Stop searching"),
+                    (Input::Back, "Stop searching"),
+                ];
             }
-            stdout().flush().unwrap();
+            vec![
+                (Input::Up, "Previous"),
+                (Input::Down, "Next"),
+                (Input::PageUp, "Page up"),
+                (Input::PageDown, "Page down"),
+                (Input::Home, "First"),
+                (Input::End, "Last"),
+                (Input::Search, "Search"),
+            ]
         }
 
-        fn update(&mut self, input: Input) -> Option<Transition> {
-            match input {
-                Input::Back => Some(Transition::Pop),
-                Input::Up => {
-                    if !self.no_jobs.get() {
-                        if self.vert_sel == 0 {
-                            self.vert_sel = u8::try_from(GAME.available_jobs.lock().unwrap().len())
-                                .unwrap()
-                                - 1;
-                        } else {
-                            self.vert_sel -= 1;
-                        }
+        /// Keeps the selection in range after the list has shrunk.
+        pub fn clamp(&mut self, len: usize) {
+            if self.selected >= len {
+                self.selected = len.saturating_sub(1);
+            }
+        }
+
+        /// Moves the selection for navigation and search inputs. `label` gives the text of the
+        /// item at an index, which is what searches are matched against, so views pass the same
+        /// function they name their items with.
+        pub fn take_input<F: Fn(usize) -> String>(
+            &mut self,
+            input: Input,
+            len: usize,
+            label: F,
+        ) -> Taken {
+            if let Some(query) = &mut self.search {
+                match input {
+                    Input::Type('\n') | Input::Select | Input::Back => {
+                        self.search = None;
+                        return Taken::Transition(Transition::InputMode(InputMode::Control));
                     }
-                    self.full_redraw();
-                    None
-                }
-                Input::Down => {
-                    if !self.no_jobs.get() {
-                        let max_idx =
-                            u8::try_from(GAME.available_jobs.lock().unwrap().len()).unwrap() - 1;
-                        if self.vert_sel == max_idx {
-                            self.vert_sel = 0;
-                        } else {
-                            self.vert_sel += 1;
-                        }
-                    };
-                    self.full_redraw();
-                    None
-                }
-                Input::Left => {
-                    self.horiz_sel = match self.horiz_sel {
-                        Name | Accept => Name,
-                        Decline => Accept,
-                    };
-                    self.full_redraw();
-                    None
-                }
-                Input::Right => {
-                    self.horiz_sel = match self.horiz_sel {
-                        Name => Accept,
-                        Accept | Decline => Decline,
-                    };
-                    self.full_redraw();
-                    None
-                }
-                Input::Select => {
-                    match self.horiz_sel {
-                        Name => {}
-                        Accept => {
-                            if !self.no_jobs.get() {
-                                GAME.accept_job_at(self.vert_sel.try_into().unwrap())
-                            }
-                            if self.vert_sel
-                                >= u8::try_from(GAME.available_jobs.lock().unwrap().len()).unwrap()
-                                && self.vert_sel > 0
-                            {
-                                self.vert_sel -= 1;
-                            }
-                            self.full_redraw();
-                        }
-                        Decline => {
-                            if !self.no_jobs.get() {
-                                GAME.decline_job_at(self.vert_sel.try_into().unwrap())
-                            }
-                            if self.vert_sel
-                                >= u8::try_from(GAME.available_jobs.lock().unwrap().len()).unwrap()
-                                && self.vert_sel > 0
-                            {
-                                self.vert_sel -= 1;
-                            }
-                            self.full_redraw();
-                        }
+                    Input::Type(c) => query.push(c),
+                    Input::BkSpace => {
+                        query.pop();
                     }
-                    None
+                    Input::Down => {
+                        let from = (self.selected + 1) % len.max(1);
+                        self.find(len, from, &label);
+                        return Taken::Redraw;
+                    }
+                    _ => return Taken::Redraw,
                 }
-                _ => None,
+                let from = self.selected;
+                self.find(len, from, &label);
+                return Taken::Redraw;
+            }
+
+            if len == 0 {
+                return Taken::Ignored;
             }
+            let last = len - 1;
+            let page = self.page.get().max(1);
+            self.selected = match input {
+                Input::Up if self.selected == 0 && self.wrap => last,
+                Input::Up => self.selected.saturating_sub(1),
+                Input::Down if self.selected == last && self.wrap => 0,
+                Input::Down => (self.selected + 1).min(last),
+                Input::PageUp => self.selected.saturating_sub(page),
+                Input::PageDown => (self.selected + page).min(last),
+                Input::Home => 0,
+                Input::End => last,
+                Input::Search => {
+                    self.search = Some(String::new());
+                    return Taken::Transition(Transition::InputMode(InputMode::Type));
+                }
+                _ => return Taken::Ignored,
+            };
+            Taken::Redraw
         }
-    }
 
-    impl View {
-        pub fn new() -> View {
-            View {
-                vert_sel: 0,
-                horiz_sel: Name,
-                no_jobs: Cell::new(GAME.available_jobs.lock().unwrap().len() == 0),
+        /// Selects the first item from `from` onwards, wrapping around, that matches the search.
+        fn find<F: Fn(usize) -> String>(&mut self, len: usize, from: usize, label: &F) {
+            let query = match &self.search {
+                Some(query) if !query.is_empty() => query.to_lowercase(),
+                _ => return,
+            };
+            if let Some(idx) = (from..len)
+                .chain(0..from)
+                .find(|&idx| label(idx).to_lowercase().contains(&query))
+            {
+                self.selected = idx;
             }
         }
-    }
-}
 
-mod tick_view {
-    use super::view_prelude::*;
-    use crate::debug_log::DEBUG;
-    use crate::GAME;
-    use std::io::stdout;
-    use std::io::Write;
-    use termion::clear;
+        /// Draws the visible items with `draw_item`, scrolling so the selection is in view, and
+        /// returns the area of each drawn item including the marker column, for hit testing.
+        pub fn draw<F: FnMut(usize, Rect)>(
+            &self,
+            area: Rect,
+            len: usize,
+            mut draw_item: F,
+        ) -> Vec<(usize, Rect)> {
+            let mut area = area;
+            if let Some(query) = &self.search {
+                if area.height > 0 {
+                    area.height -= 1;
+                    let prompt = Rect::new(area.x, area.y + area.height, area.width, 1);
+                    print_clipped(prompt, &format!("/{}", query));
+                }
+            }
 
-    pub struct View;
+            let page = (area.height / self.item_height.max(1)) as usize;
+            self.page.set(page);
+            let mut offset = self.offset.get().min(len.saturating_sub(page));
+            if self.selected < offset {
+                offset = self.selected;
+            } else if page > 0 && self.selected >= offset + page {
+                offset = self.selected + 1 - page;
+            }
+            self.offset.set(offset);
 
-    impl FullView for View {
-        fn full_redraw(&self) {
-            print!("{}", clear::All);
-            stdout().flush().unwrap();
-        }
-        fn update(&mut self, _: Input) -> Option<Transition> {
-            unreachable!()
-        }
-        fn start(&mut self) -> Option<Transition> {
-            GAME.tick();
-            DEBUG.log("Ticked");
-            Some(Transition::Pop)
+            let scrollbar = len > page;
+            let items = if scrollbar {
+                Rect::new(area.x, area.y, area.width.saturating_sub(1), area.height)
+            } else {
+                area
+            };
+
+            let drawn = (offset..len.min(offset + page))
+                .map(|idx| {
+                    let row = Rect::new(
+                        items.x,
+                        items.y + (idx - offset) as u16 * self.item_height,
+                        items.width,
+                        self.item_height,
+                    );
+                    if self.focused && idx == self.selected {
+                        print_clipped(row, selection_marker());
+                    }
+                    draw_item(idx, row.indent(ScrollList::MARKER_WIDTH));
+                    (idx, row)
+                })
+                .collect();
+
+            if scrollbar {
+                self.draw_scrollbar(
+                    Rect::new(area.x + area.width - 1, area.y, 1, area.height),
+                    len,
+                    page,
+                    offset,
+                );
+            }
+            drawn
         }
-    }
 
-    impl View {
-        pub fn new() -> View {
-            View
+        fn draw_scrollbar(&self, area: Rect, len: usize, page: usize, offset: usize) {
+            let (track, thumb) = (glyphs::SCROLL_TRACK.get(), glyphs::SCROLL_THUMB.get());
+            let height = area.height as usize;
+            if height == 0 {
+                return;
+            }
+            let thumb_len = (height * page / len).max(1);
+            let thumb_start = if len > page {
+                (height - thumb_len) * offset / (len - page)
+            } else {
+                0
+            };
+            for row in 0..height {
+                let glyph = if row >= thumb_start && row < thumb_start + thumb_len {
+                    thumb
+                } else {
+                    track
+                };
+                print_clipped(area.line(row as u16), glyph);
+            }
         }
     }
 }
 
-mod rockets_view {
-    use super::view_prelude::*;
+pub mod type_box {
+    use super::layout::Rect;
+    use super::widget::{Label, Widget};
+    use super::{Action, Input};
+    use crate::font_metrics;
     use crate::ui_print;
-    use std::io::stdout;
-    use std::io::Write;
-    use termion::{clear, cursor};
+    use crate::units::Dimension;
+    use crate::units::Quantity;
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
 
-    pub struct View {
-        sel: Sel,
+    pub struct TypeBox {
+        content: String,
+        /// The cursor, as a number of characters from the start of the content.
+        cursor: usize,
+        /// The first column and line in view, counted in characters and lines.
+        scroll: (usize, usize),
+        width: u16,
+        lines: u16,
+        /// Where the box was last drawn, so the cursor can be placed after the rest is drawn.
+        loc: Cell<(u16, u16)>,
+        active: bool,
+        validators: Vec<Validator>,
+        /// Why the content was refused, until it is fixed.
+        error: Option<String>,
+        history: Option<&'static Mutex<History>>,
+        /// How far back the history has been recalled, and what was typed before it was.
+        recalled: Option<(usize, String)>,
     }
 
-    enum Sel {
-        New,
-        Rocket(u8),
-        RocketEdit(u8),
+    /// A check on what is typed, made when it is submitted.
+    pub enum Validator {
+        NonEmpty,
+        /// A whole number.
+        Numeric,
+        /// A number followed by a unit of the dimension, such as "12 m³".
+        Quantity(Dimension),
+        /// None of the names, ignoring case.
+        Unique(Vec<String>),
     }
 
-    impl FullView for View {
-        fn full_redraw(&self) {
-            print!("{}{}", clear::All, cursor::Goto(1, 1));
+    /// What has been submitted in the boxes sharing it, newest last.
+    pub struct History {
+        entries: VecDeque<String>,
+    }
 
-            print!("Rockets  (+)");
+    type ShouldRedraw = bool;
 
-            let rockets = GAME.rocket_designs.lock().unwrap();
+    impl TypeBox {
+        pub fn activate(&mut self, activate: bool) {
+            if self.active && !activate {
+                ui_print!("{}{}", termion::cursor::Hide, termion::cursor::Restore);
+            }
+
+            if !self.active && activate {
+                ui_print!("{}", termion::cursor::BlinkingBlock)
+            }
+            self.active = activate;
+        }
+
+        /// What the box does with the inputs it takes while active, for the help.
+        pub fn actions(&self) -> Vec<Action> {
+            let mut actions = vec![
+                (Input::Left, "Move the cursor left"),
+                (Input::Right, "Move the cursor right"),
+                (Input::WordLeft, "Move back a word"),
+                (Input::WordRight, "Move forward a word"),
+                (Input::Home, "Start of the line"),
+                (Input::End, "End of the line"),
+                (Input::BkSpace, "Delete before the cursor"),
+                (Input::Del, "Delete after the cursor"),
+                (Input::WordBkSpace, "Delete the word before the cursor"),
+                (Input::WordDel, "Delete the word after the cursor"),
+            ];
+            if self.lines > 1 {
+                actions.extend_from_slice(&[
+                    (Input::Up, "Line up"),
+                    (Input::Down, "Line down"),
+                    (Input::Type('\n'), "New line"),
+                ]);
+            } else if self.history.is_some() {
+                actions.extend_from_slice(&[
+                    (Input::Up, "Previous entry"),
+                    (Input::Down, "Next entry"),
+                ]);
+            }
+            actions
+        }
+
+        pub fn take_input(&mut self, input: &Input) -> ShouldRedraw {
+            if !self.active {
+                return false;
+            }
+            let chars: Vec<char> = self.content.chars().collect();
+            match *input {
+                Input::Type('\n') if self.lines == 1 => return false,
+                Input::Type(c) => {
+                    self.content.insert(self.byte_idx(self.cursor), c);
+                    self.cursor += 1;
+                    self.edited();
+                }
+                Input::Left => self.cursor = self.cursor.saturating_sub(1),
+                Input::Right => self.cursor = (self.cursor + 1).min(chars.len()),
+                Input::WordLeft => self.cursor = word_start(&chars, self.cursor),
+                Input::WordRight => self.cursor = word_end(&chars, self.cursor),
+                Input::Home => self.cursor -= self.row_col().1,
+                Input::End => {
+                    let rest = chars[self.cursor..].iter().take_while(|&&c| c != '\n');
+                    self.cursor += rest.count();
+                }
+                Input::BkSpace if self.cursor > 0 => {
+                    self.cursor -= 1;
+                    self.content.remove(self.byte_idx(self.cursor));
+                    self.edited();
+                }
+                Input::Del if self.cursor < chars.len() => {
+                    self.content.remove(self.byte_idx(self.cursor));
+                    self.edited();
+                }
+                Input::WordBkSpace => {
+                    let start = word_start(&chars, self.cursor);
+                    self.remove(start, self.cursor);
+                    self.cursor = start;
+                }
+                Input::WordDel => {
+                    let end = word_end(&chars, self.cursor);
+                    self.remove(self.cursor, end);
+                }
+                Input::BkSpace | Input::Del => {}
+                Input::Up | Input::Down if self.lines > 1 => {
+                    let (row, col) = self.row_col();
+                    let row = match input {
+                        Input::Up if row > 0 => row - 1,
+                        Input::Down if row + 1 < self.content.split('\n').count() => row + 1,
+                        _ => row,
+                    };
+                    self.move_to(row, col);
+                }
+                Input::Up => return self.recall(true),
+                Input::Down => return self.recall(false),
+                _ => return false,
+            }
+            self.follow_cursor();
+            true
+        }
+
+        pub fn before_render(&self) {
+            if self.active {
+                ui_print!("{}", termion::cursor::Hide);
+            }
+        }
+
+        pub fn after_render(&self) {
+            if self.active {
+                let (x, y) = self.loc.get();
+                let (row, col) = self.row_col();
+                let line = self.content.split('\n').nth(row).unwrap_or("");
+                let before: String = line.chars().take(col).skip(self.scroll.0).collect();
+                ui_print!(
+                    "{}{}",
+                    termion::cursor::Goto(
+                        x + font_metrics::width(&before),
+                        y + (row - self.scroll.1) as u16
+                    ),
+                    termion::cursor::Show
+                );
+            }
+        }
+
+        pub fn new() -> TypeBox {
+            TypeBox {
+                content: String::new(),
+                cursor: 0,
+                scroll: (0, 0),
+                width: 0,
+                lines: 1,
+                loc: Cell::new((1, 1)),
+                active: false,
+                validators: Vec::new(),
+                error: None,
+                history: None,
+                recalled: None,
+            }
+        }
+
+        pub fn with_width(self, width: u16) -> TypeBox {
+            TypeBox { width, ..self }
+        }
+
+        /// Makes the box take new lines, showing this many at once.
+        pub fn multi_line(self, lines: u16) -> TypeBox {
+            TypeBox { lines, ..self }
+        }
+
+        pub fn validate(mut self, validator: Validator) -> TypeBox {
+            self.validators.push(validator);
+            self
+        }
+
+        /// Lets Up and Down recall what was submitted before, in this or any box sharing the
+        /// history.
+        pub fn with_history(self, history: &'static Mutex<History>) -> TypeBox {
+            TypeBox {
+                history: Some(history),
+                ..self
+            }
+        }
+
+        /// The number of columns the box takes up.
+        pub fn width(&self) -> u16 {
+            self.width
+        }
+
+        /// The number of lines the box takes up, not counting the error.
+        pub fn height(&self) -> u16 {
+            self.lines
+        }
+
+        pub fn content(&self) -> &str {
+            &self.content
+        }
+
+        /// Replaces the content, putting the cursor at the end.
+        pub fn set_content<S: Into<String>>(&mut self, content: S) {
+            self.content = content.into();
+            self.cursor = self.content.chars().count();
+            self.scroll = (0, 0);
+            self.error = None;
+            self.recalled = None;
+            self.follow_cursor();
+        }
+
+        /// Checks the content, returning it and adding it to the history if it passes. If it
+        /// does not the error is shown until it is fixed.
+        pub fn submit(&mut self) -> Option<String> {
+            self.error = self.check().err();
+            if self.error.is_some() {
+                return None;
+            }
+            if let Some(history) = self.history {
+                history.lock().unwrap().push(&self.content);
+            }
+            self.recalled = None;
+            Some(self.content.clone())
+        }
+
+        /// Why the content was refused, if it was and has not been fixed since.
+        pub fn error(&self) -> Option<&str> {
+            self.error.as_deref()
+        }
+
+        /// Draws the error, if there is one, or clears the line.
+        pub fn draw_error(&self, area: Rect) {
+            let error = self.error().map(|e| format!("! {}", e));
+            Label::new(error.unwrap_or_default()).draw(area.line(0));
+        }
+
+        fn check(&self) -> Result<(), String> {
+            self.validators
+                .iter()
+                .try_for_each(|validator| validator.check(&self.content))
+        }
+
+        /// Keeps a shown error up to date with the content, and stops recalling the history.
+        fn edited(&mut self) {
+            self.recalled = None;
+            if self.error.is_some() {
+                self.error = self.check().err();
+            }
+        }
+
+        fn remove(&mut self, start: usize, end: usize) {
+            if start < end {
+                let range = self.byte_idx(start)..self.byte_idx(end);
+                self.content.replace_range(range, "");
+                self.edited();
+            }
+        }
+
+        /// Swaps the content for an older entry in the history, or a newer one, or back to what
+        /// was typed once past the newest.
+        fn recall(&mut self, older: bool) -> ShouldRedraw {
+            let history = match self.history {
+                Some(history) => history.lock().unwrap(),
+                None => return false,
+            };
+            let len = history.entries.len();
+            let (back, typed) = match self.recalled.take() {
+                Some((back, typed)) => (Some(back), typed),
+                None => (None, self.content.clone()),
+            };
+            let back = match (back, older) {
+                (None, true) if len > 0 => Some(0),
+                (Some(back), true) if back + 1 < len => Some(back + 1),
+                (Some(back), false) => back.checked_sub(1),
+                (back, _) => back,
+            };
+            let content = match back {
+                Some(back) => history.entries[len - 1 - back].clone(),
+                None => typed.clone(),
+            };
+            drop(history);
+            self.set_content(content);
+            self.recalled = back.map(|back| (back, typed));
+            true
+        }
+
+        /// The byte the character at `idx` starts at.
+        fn byte_idx(&self, idx: usize) -> usize {
+            self.content
+                .char_indices()
+                .nth(idx)
+                .map_or(self.content.len(), |(byte, _)| byte)
+        }
+
+        /// The line the cursor is on, and how many characters along it.
+        fn row_col(&self) -> (usize, usize) {
+            let mut row_col = (0, 0);
+            for c in self.content.chars().take(self.cursor) {
+                row_col = match c {
+                    '\n' => (row_col.0 + 1, 0),
+                    _ => (row_col.0, row_col.1 + 1),
+                };
+            }
+            row_col
+        }
+
+        /// Moves the cursor to `col` characters along `row`, or the end of the line if it is
+        /// shorter.
+        fn move_to(&mut self, row: usize, col: usize) {
+            let mut start = 0;
+            for (idx, line) in self.content.split('\n').enumerate() {
+                let len = line.chars().count();
+                if idx == row {
+                    self.cursor = start + col.min(len);
+                    return;
+                }
+                start += len + 1;
+            }
+        }
+
+        /// Scrolls so the cursor is in view, with room for it after the last character.
+        fn follow_cursor(&mut self) {
+            let (row, col) = self.row_col();
+            let line: Vec<char> = self
+                .content
+                .split('\n')
+                .nth(row)
+                .unwrap_or("")
+                .chars()
+                .collect();
+            let (mut x, mut y) = self.scroll;
+            x = x.min(col);
+            while x < col
+                && font_metrics::width(&line[x..col].iter().collect::<String>()) >= self.width
+            {
+                x += 1;
+            }
+            y = y.min(row);
+            if row >= y + self.lines as usize {
+                y = row + 1 - self.lines as usize;
+            }
+            self.scroll = (x, y);
+        }
+    }
+
+    /// Where the word before `idx` starts, skipping any spaces before it.
+    fn word_start(chars: &[char], mut idx: usize) -> usize {
+        while idx > 0 && chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        while idx > 0 && !chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        idx
+    }
+
+    /// Where the word after `idx` ends, skipping any spaces before it.
+    fn word_end(chars: &[char], mut idx: usize) -> usize {
+        while idx < chars.len() && chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        while idx < chars.len() && !chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        idx
+    }
+
+    impl Widget for TypeBox {
+        fn draw(&self, area: Rect) {
+            use termion::style;
+            if area.is_empty() {
+                return;
+            }
+            self.loc.set((area.x, area.y));
+            if self.active {
+                ui_print!("{}", style::Invert);
+            }
+            let width = area.width.min(self.width());
+            let mut lines = self.content.split('\n').skip(self.scroll.1);
+            for row in 0..area.height.min(self.lines) {
+                let line = lines.next().unwrap_or("");
+                let visible = match line.char_indices().nth(self.scroll.0) {
+                    Some((idx, _)) => &line[idx..],
+                    None => "",
+                };
+                let shown = font_metrics::truncate(visible, width);
+                ui_print!(
+                    "{}{}{}",
+                    termion::cursor::Goto(area.x, area.y + row),
+                    shown,
+                    " ".repeat((width - font_metrics::width(shown)) as usize)
+                );
+            }
+            if self.active {
+                ui_print!("{}", style::NoInvert);
+            }
+        }
+    }
+
+    impl Validator {
+        fn check(&self, text: &str) -> Result<(), String> {
+            let text = text.trim();
+            let ok = match self {
+                Validator::NonEmpty => !text.is_empty(),
+                Validator::Numeric => text.parse::<u64>().is_ok(),
+                Validator::Quantity(dimension) => {
+                    Quantity::parse(text).map(Quantity::dimension) == Some(*dimension)
+                }
+                Validator::Unique(names) => {
+                    let text = text.to_lowercase();
+                    !names.iter().any(|name| name.trim().to_lowercase() == text)
+                }
+            };
+            if ok {
+                return Ok(());
+            }
+            Err(match self {
+                Validator::NonEmpty => "Can not be empty".to_string(),
+                Validator::Numeric => "Must be a whole number".to_string(),
+                Validator::Quantity(dimension) => {
+                    format!(
+                        "Must be a number of {}",
+                        Quantity::units(*dimension).join(", ")
+                    )
+                }
+                Validator::Unique(_) => "Already taken".to_string(),
+            })
+        }
+    }
+
+    impl History {
+        /// The number of entries kept before the oldest are forgotten.
+        const LEN: usize = 20;
+
+        pub fn new() -> History {
+            History {
+                entries: VecDeque::new(),
+            }
+        }
+
+        /// Adds an entry as the newest, moving it there if it was already in the history.
+        fn push(&mut self, entry: &str) {
+            if entry.is_empty() {
+                return;
+            }
+            self.entries.retain(|e| e != entry);
+            if self.entries.len() == History::LEN {
+                self.entries.pop_front();
+            }
+            self.entries.push_back(entry.to_string());
+        }
+    }
+}
+
+pub mod dialog {
+    use super::layout::{Rect, Size};
+    use super::scroll_list::ScrollList;
+    use super::type_box::TypeBox;
+    use super::widget::{Button, Label, Panel, Widget};
+    use super::{HitMap, HitRegion, Input, InputMode};
+    use crate::font_metrics;
+
+    /// A small box drawn over the current view, which asks the player something.
+    pub trait Dialog {
+        /// The size of the box, as `(width, height)`, including its border.
+        fn size(&self) -> (u16, u16);
+        fn draw(&self, area: Rect);
+        /// Takes an input, returning the answer once there is one.
+        fn update(&mut self, input: Input) -> Option<DialogResult>;
+        fn input_mode(&self) -> InputMode {
+            InputMode::Control
+        }
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            Vec::new()
+        }
+        fn click(&mut self, _target: usize) -> Option<DialogResult> {
+            None
+        }
+    }
+
+    /// The answer to a dialog, with a variant for each kind of dialog.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum DialogResult {
+        /// Whether a `Confirm` was answered yes.
+        Confirmed(bool),
+        /// An `Alert` was dismissed.
+        Dismissed,
+        /// The text entered in a `Prompt`, or `None` if it was cancelled.
+        Text(Option<String>),
+        /// The index of the option picked from a `Choice`, or `None` if it was cancelled.
+        Picked(Option<usize>),
+        /// The command picked in the palette with the number typed after it, or `None` if it was
+        /// cancelled.
+        Command(Option<(usize, Option<u32>)>),
+    }
+
+    /// Asks a yes or no question, defaulting to no.
+    pub struct Confirm {
+        message: String,
+        yes: bool,
+        hits: HitMap<bool>,
+    }
+
+    /// Tells the player something, until they dismiss it. The message can run over several lines.
+    pub struct Alert {
+        message: String,
+    }
+
+    /// Asks for a line of text.
+    pub struct Prompt {
+        message: String,
+        text: TypeBox,
+    }
+
+    /// Asks the player to pick one of a list of options.
+    pub struct Choice {
+        title: String,
+        options: Vec<String>,
+        list: ScrollList,
+        hits: HitMap<usize>,
+    }
+
+    /// Lists what each key does, until it is dismissed.
+    pub struct Help {
+        title: String,
+        /// The names of the keys for each action, and what it does.
+        lines: Vec<(String, &'static str)>,
+        list: ScrollList,
+    }
+
+    /// Draws the border and message shared by the dialogs with a message, returning the line
+    /// below the message.
+    fn draw_message(area: Rect, message: &str) -> Rect {
+        Panel::new("").draw(area);
+        let inner = Panel::inner(area).indent(1);
+        let mut row = 0;
+        for line in message.lines() {
+            Label::new(line).draw(inner.line(row));
+            row += 1;
+        }
+        inner.line(row.max(1))
+    }
+
+    impl Confirm {
+        pub fn new<S: Into<String>>(message: S) -> Confirm {
+            Confirm {
+                message: message.into(),
+                yes: false,
+                hits: HitMap::new(),
+            }
+        }
+    }
+
+    impl Dialog for Confirm {
+        fn size(&self) -> (u16, u16) {
+            (font_metrics::width(&self.message) + 4, 4)
+        }
+
+        fn draw(&self, area: Rect) {
+            self.hits.clear();
+            let line = draw_message(area, &self.message);
+            let yes = Button::new("YES").focused(self.yes);
+            let no = Button::new("NO").focused(!self.yes);
+            let buttons = line.columns(&[
+                Size::Fill,
+                Size::Fixed(yes.width()),
+                Size::Fixed(5),
+                Size::Fixed(no.width()),
+                Size::Fill,
+            ]);
+            yes.draw(buttons[1]);
+            no.draw(buttons[3]);
+            self.hits.add(buttons[1], true);
+            self.hits.add(buttons[3], false);
+        }
+
+        fn update(&mut self, input: Input) -> Option<DialogResult> {
+            match input {
+                Input::Left => {
+                    self.yes = true;
+                    None
+                }
+                Input::Right => {
+                    self.yes = false;
+                    None
+                }
+                Input::Select => Some(DialogResult::Confirmed(self.yes)),
+                Input::Back => Some(DialogResult::Confirmed(false)),
+                _ => None,
+            }
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
+        }
+
+        fn click(&mut self, target: usize) -> Option<DialogResult> {
+            self.yes = self.hits.get(target)?;
+            self.update(Input::Select)
+        }
+    }
+
+    impl Alert {
+        pub fn new<S: Into<String>>(message: S) -> Alert {
+            Alert {
+                message: message.into(),
+            }
+        }
+    }
+
+    impl Dialog for Alert {
+        fn size(&self) -> (u16, u16) {
+            let lines = self.message.lines();
+            let width = lines.clone().map(font_metrics::width).max().unwrap_or(0);
+            (width + 4, lines.count().max(1) as u16 + 3)
+        }
+
+        fn draw(&self, area: Rect) {
+            let line = draw_message(area, &self.message);
+            let ok = Button::new("OK").focused(true);
+            let cols = line.columns(&[Size::Fill, Size::Fixed(ok.width()), Size::Fill]);
+            ok.draw(cols[1]);
+        }
+
+        fn update(&mut self, input: Input) -> Option<DialogResult> {
+            match input {
+                Input::Select | Input::Back => Some(DialogResult::Dismissed),
+                _ => None,
+            }
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            //The whole box dismisses it
+            let (width, height) = self.size();
+            vec![HitRegion {
+                area: Rect::screen().centered(width, height),
+                target: 0,
+            }]
+        }
+
+        fn click(&mut self, _target: usize) -> Option<DialogResult> {
+            Some(DialogResult::Dismissed)
+        }
+    }
+
+    impl Prompt {
+        /// Asks for the text typed in `text`, which is checked by its validators before the
+        /// prompt closes.
+        pub fn new<S: Into<String>>(message: S, mut text: TypeBox) -> Prompt {
+            text.activate(true);
+            Prompt {
+                message: message.into(),
+                text,
+            }
+        }
+    }
+
+    impl Dialog for Prompt {
+        fn size(&self) -> (u16, u16) {
+            let width = font_metrics::width(&self.message).max(self.text.width());
+            let error = self.text.error().map_or(0, |e| font_metrics::width(e) + 2);
+            let height = self.text.height() + self.text.error().map_or(0, |_| 1);
+            (width.max(error) + 4, height + 3)
+        }
+
+        fn draw(&self, area: Rect) {
+            self.text.before_render();
+            draw_message(area, &self.message);
+            let inner = Panel::inner(area).indent(1);
+            self.text.draw(inner.rows(&[Size::Fixed(1), Size::Fill])[1]);
+            if self.text.error().is_some() {
+                self.text.draw_error(inner.line(1 + self.text.height()));
+            }
+            self.text.after_render();
+        }
+
+        fn update(&mut self, input: Input) -> Option<DialogResult> {
+            let submitted = match input {
+                //Enter starts a new line in boxes with more than one
+                Input::Type('\n') => self.text.height() == 1,
+                Input::Select => true,
+                _ => false,
+            };
+            match input {
+                _ if submitted => {
+                    let text = self.text.submit()?;
+                    self.text.activate(false);
+                    Some(DialogResult::Text(Some(text)))
+                }
+                Input::Back => {
+                    self.text.activate(false);
+                    Some(DialogResult::Text(None))
+                }
+                _ => {
+                    self.text.take_input(&input);
+                    None
+                }
+            }
+        }
+
+        fn input_mode(&self) -> InputMode {
+            InputMode::Type
+        }
+    }
+
+    impl Choice {
+        pub fn new<S: Into<String>>(title: S, options: Vec<String>) -> Choice {
+            Choice {
+                title: title.into(),
+                options,
+                list: ScrollList::new(1).wrapping(),
+                hits: HitMap::new(),
+            }
+        }
+    }
+
+    impl Dialog for Choice {
+        fn size(&self) -> (u16, u16) {
+            let widest = self
+                .options
+                .iter()
+                .map(|option| font_metrics::width(option) + 2)
+                .chain(Some(font_metrics::width(&self.title)))
+                .max()
+                .unwrap_or(0);
+            (widest + 4, self.options.len() as u16 + 2)
+        }
+
+        fn draw(&self, area: Rect) {
+            self.hits.clear();
+            Panel::new(self.title.as_str()).draw(area);
+            let drawn = self.list.draw(
+                Panel::inner(area).indent(1),
+                self.options.len(),
+                |idx, area| Label::new(self.options[idx].as_str()).draw(area),
+            );
+            for (idx, area) in drawn {
+                self.hits.add(area, idx);
+            }
+        }
+
+        fn update(&mut self, input: Input) -> Option<DialogResult> {
+            match input {
+                Input::Select => return Some(DialogResult::Picked(Some(self.list.selected))),
+                Input::Back => return Some(DialogResult::Picked(None)),
+                //Searching needs the input mode changed, which a dialog can not do
+                Input::Search => return None,
+                _ => {}
+            }
+            self.list
+                .take_input(input, self.options.len(), |_| String::new());
+            None
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
+        }
+
+        fn click(&mut self, target: usize) -> Option<DialogResult> {
+            self.list.selected = self.hits.get(target)?;
+            self.update(Input::Select)
+        }
+    }
+
+    impl Help {
+        pub fn new<S: Into<String>>(title: S, lines: Vec<(String, &'static str)>) -> Help {
+            Help {
+                title: title.into(),
+                lines,
+                list: ScrollList::new(1),
+            }
+        }
+
+        /// The width of the column the keys are listed in.
+        fn keys_width(&self) -> u16 {
+            self.lines
+                .iter()
+                .map(|(keys, _)| font_metrics::width(keys))
+                .max()
+                .unwrap_or(0)
+        }
+    }
+
+    impl Dialog for Help {
+        fn size(&self) -> (u16, u16) {
+            let descriptions = self
+                .lines
+                .iter()
+                .map(|(_, description)| font_metrics::width(description))
+                .max()
+                .unwrap_or(0);
+            //Room for the marker, the gap between the columns and the scroll bar
+            let width =
+                (self.keys_width() + descriptions + 5).max(font_metrics::width(&self.title));
+            //Long lists scroll, and long lines are cut short, rather than needing a bigger
+            //terminal
+            let screen = Rect::screen();
+            let height = (self.lines.len() as u16 + 2).min(screen.height.saturating_sub(2));
+            ((width + 4).min(screen.width), height.max(3))
+        }
+
+        fn draw(&self, area: Rect) {
+            Panel::new(self.title.as_str()).draw(area);
+            let keys_width = self.keys_width();
+            self.list.draw(
+                Panel::inner(area).indent(1),
+                self.lines.len(),
+                |idx, area| {
+                    let (keys, description) = &self.lines[idx];
+                    let cols = area.columns(&[Size::Fixed(keys_width + 2), Size::Fill]);
+                    Label::new(keys.as_str()).draw(cols[0]);
+                    Label::new(*description).draw(cols[1]);
+                },
+            );
+        }
+
+        fn update(&mut self, input: Input) -> Option<DialogResult> {
+            match input {
+                Input::Select | Input::Back | Input::Help => return Some(DialogResult::Dismissed),
+                Input::Search => return None,
+                _ => {}
+            }
+            self.list
+                .take_input(input, self.lines.len(), |_| String::new());
+            None
+        }
+    }
+}
+
+pub mod palette {
+    use super::dialog::{Dialog, DialogResult};
+    use super::layout::{Rect, Size};
+    use super::scroll_list::ScrollList;
+    use super::type_box::TypeBox;
+    use super::widget::{Label, Panel, Widget};
+    use super::{HitMap, HitRegion, Input, InputMode, Transition};
+    use crate::font_metrics;
+    use std::cmp::Reverse;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    /// Something the player can do from the palette, by typing its name.
+    pub struct Command {
+        pub name: String,
+        /// What the number typed after the name means, for commands that take one.
+        pub arg: Option<&'static str>,
+        pub run: Run,
+    }
+
+    pub enum Run {
+        /// Runs the same whatever the current view is.
+        /// Shared so it can be run without holding on to `COMMANDS`.
+        Anywhere(Arc<dyn Fn(Option<u32>) -> Ran + Send + Sync>),
+        /// Handed to the current view's `run_command` with this id.
+        InView(usize),
+    }
+
+    /// The transition a command makes, or why it could not be run.
+    pub type Ran = Result<Option<Transition>, String>;
+
+    lazy_static! {
+        /// The commands that can be run from any view.
+        pub static ref COMMANDS: Mutex<Vec<Command>> = {
+            let mut commands = super::basic_tl_view::commands();
+            commands.extend(super::rockets_view::commands());
+            commands.extend(crate::Game::commands());
+            Mutex::new(commands)
+        };
+    }
+
+    impl Command {
+        pub fn anywhere<F>(name: &str, arg: Option<&'static str>, run: F) -> Command
+        where
+            F: Fn(Option<u32>) -> Ran + Send + Sync + 'static,
+        {
+            Command {
+                name: name.to_string(),
+                arg,
+                run: Run::Anywhere(Arc::new(run)),
+            }
+        }
+
+        pub fn in_view(name: &str, arg: Option<&'static str>, id: usize) -> Command {
+            Command {
+                name: name.to_string(),
+                arg,
+                run: Run::InView(id),
+            }
+        }
+    }
+
+    /// How well `query` matches `name`, or `None` if it does not. Every letter of the query has
+    /// to appear in the name in order, and letters that start a word or follow the previous match
+    /// score higher, so "acj" finds "accept job".
+    pub fn score(query: &str, name: &str) -> Option<u32> {
+        let name: Vec<char> = name.to_lowercase().chars().collect();
+        let mut score = 0;
+        let mut next = 0;
+        let mut last = None;
+        for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+            let found = next + name[next..].iter().position(|&n| n == c)?;
+            score += 1;
+            if found == 0 || name[found - 1] == ' ' {
+                score += 3;
+            }
+            if last.map(|last| last + 1) == Some(found) {
+                score += 2;
+            }
+            last = Some(found);
+            next = found + 1;
+        }
+        Some(score)
+    }
+
+    /// Splits a number off the end of what was typed, as the command's argument.
+    fn split_arg(typed: &str) -> (&str, Option<u32>) {
+        let typed = typed.trim();
+        match typed.rsplit_once(' ') {
+            Some((query, last)) => match last.parse() {
+                Ok(arg) => (query, Some(arg)),
+                Err(_) => (typed, None),
+            },
+            None => (typed, None),
+        }
+    }
+
+    /// Finds a command by name as it is typed.
+    pub struct Palette {
+        /// The name and argument of each command, in the order they are picked by.
+        commands: Vec<(String, Option<&'static str>)>,
+        text: TypeBox,
+        /// The indices of the commands matching what has been typed, best first.
+        matches: Vec<usize>,
+        list: ScrollList,
+        hits: HitMap<usize>,
+    }
+
+    impl Palette {
+        /// The number of matches shown at once.
+        const SHOWN: u16 = 8;
+        const LEN: u16 = 30;
+
+        pub fn new(commands: Vec<(String, Option<&'static str>)>) -> Palette {
+            let mut text = TypeBox::new().with_width(Palette::LEN);
+            text.activate(true);
+            let mut palette = Palette {
+                commands,
+                text,
+                matches: Vec::new(),
+                list: ScrollList::new(1),
+                hits: HitMap::new(),
+            };
+            palette.find();
+            palette
+        }
+
+        /// Matches the commands against what has been typed, keeping the best at the top.
+        fn find(&mut self) {
+            let (query, arg) = split_arg(self.text.content());
+            let mut scored: Vec<(u32, usize)> = self
+                .commands
+                .iter()
+                .enumerate()
+                //A number is only typed after commands that take one
+                .filter(|(_, (_, takes))| arg.is_none() || takes.is_some())
+                .filter_map(|(idx, (name, _))| Some((score(query, name)?, idx)))
+                .collect();
+            //The sort is stable, so equally good matches stay in the order they were registered
+            scored.sort_by_key(|&(score, _)| Reverse(score));
+            self.matches = scored.into_iter().map(|(_, idx)| idx).collect();
+            self.list.selected = 0;
+        }
+
+        fn label(&self, idx: usize) -> String {
+            match &self.commands[idx] {
+                (name, Some(arg)) => format!("{} <{}>", name, arg),
+                (name, None) => name.clone(),
+            }
+        }
+
+        fn picked(&mut self) -> DialogResult {
+            self.text.activate(false);
+            let (_, arg) = split_arg(self.text.content());
+            let idx = self.matches.get(self.list.selected);
+            DialogResult::Command(idx.map(|&idx| (idx, arg)))
+        }
+    }
+
+    impl Dialog for Palette {
+        fn size(&self) -> (u16, u16) {
+            let widest = (0..self.commands.len())
+                .map(|idx| font_metrics::width(&self.label(idx)) + 2)
+                .max()
+                .unwrap_or(0)
+                .max(Palette::LEN + 2);
+            let shown = (self.commands.len() as u16).clamp(1, Palette::SHOWN);
+            let screen = Rect::screen();
+            ((widest + 5).min(screen.width), shown + 3)
+        }
+
+        fn draw(&self, area: Rect) {
+            self.hits.clear();
+            self.text.before_render();
+            Panel::new("Command").draw(area);
+            let rows = Panel::inner(area)
+                .indent(1)
+                .rows(&[Size::Fixed(1), Size::Fill]);
+            let prompt = rows[0].columns(&[Size::Fixed(2), Size::Fill]);
+            Label::new(">").draw(prompt[0]);
+            self.text.draw(prompt[1]);
+            if self.matches.is_empty() {
+                Label::new("No matching command").draw(rows[1].line(0));
+            } else {
+                let drawn = self.list.draw(rows[1], self.matches.len(), |idx, area| {
+                    Label::new(self.label(self.matches[idx])).draw(area)
+                });
+                for (idx, area) in drawn {
+                    self.hits.add(area, idx);
+                }
+            }
+            self.text.after_render();
+        }
+
+        fn update(&mut self, input: Input) -> Option<DialogResult> {
+            match input {
+                Input::Type('\n') | Input::Select => return Some(self.picked()),
+                Input::Back | Input::Palette => {
+                    self.text.activate(false);
+                    return Some(DialogResult::Command(None));
+                }
+                Input::Up | Input::Down | Input::PageUp | Input::PageDown => {
+                    self.list
+                        .take_input(input, self.matches.len(), |_| String::new());
+                }
+                _ => {
+                    if self.text.take_input(&input) {
+                        self.find();
+                    }
+                }
+            }
+            None
+        }
+
+        fn input_mode(&self) -> InputMode {
+            InputMode::Type
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
+        }
+
+        fn click(&mut self, target: usize) -> Option<DialogResult> {
+            self.list.selected = self.hits.get(target)?;
+            Some(self.picked())
+        }
+    }
+}
+
+mod basic_tl_view {
+    use super::view_prelude::*;
+    use std::convert::TryInto;
+    use termion::clear;
+
+    pub struct View {
+        title: &'static str,
+        selection: u8,
+        tabs: Vec<Tab>,
+        hits: HitMap<u8>,
+    }
+
+    struct Tab {
+        name: &'static str,
+        /// Builds the view the tab opens, or `None` for the tab that asks to exit.
+        open: Option<Factory>,
+    }
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            self.hits.clear();
+            ui_print!("{}", clear::All);
+            let list = List {
+                selected: Some(usize::from(self.selection)),
+                item_height: 1,
+            };
+            let drawn = list.draw(Rect::view(), self.tabs.len(), |idx, area| {
+                Label::new(self.tabs[idx].name).draw(area)
+            });
+            for (idx, area) in drawn {
+                self.hits.add(area, idx as u8);
+            }
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            match input {
+                Input::Up => {
+                    if self.selection == 0 {
+                        self.selection = self.max_selection();
+                    } else {
+                        self.selection -= 1;
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::Down => {
+                    if self.selection == self.max_selection() {
+                        self.selection = 0;
+                    } else {
+                        self.selection += 1;
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::Select => match self.tabs[usize::from(self.selection)].open {
+                    Some(open) => Some(Transition::Push(open())),
+                    None => Some(View::confirm_exit()),
+                },
+                Input::Back => Some(View::confirm_exit()),
+                _ => None,
+            }
+        }
+
+        fn title(&self) -> String {
+            self.title.to_string()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            vec![
+                (Input::Up, "Previous"),
+                (Input::Down, "Next"),
+                (Input::Select, "Open"),
+                (Input::Back, "Exit"),
+            ]
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
+        }
+
+        fn click(&mut self, target: usize) -> Option<Transition> {
+            self.selection = self.hits.get(target)?;
+            self.update(Input::Select)
+        }
+        fn dialog_result(&mut self, result: DialogResult) -> Option<Transition> {
+            match result {
+                //Popping the last view ends the game
+                DialogResult::Confirmed(true) => Some(Transition::Pop),
+                _ => None,
+            }
+        }
+    }
+
+    impl View {
+        pub fn new() -> View {
+            View {
+                title: "Cuneiforbits",
+                selection: 0,
+                hits: HitMap::new(),
+                tabs: tabs(),
+            }
+        }
+
+        fn confirm_exit() -> Transition {
+            Transition::Dialog(Box::new(Confirm::new("Are you sure you want to exit?")))
+        }
+
+        fn max_selection(&self) -> u8 {
+            (self.tabs.len() - 1).try_into().unwrap()
+        }
+    }
+
+    impl Tab {
+        fn new(name: &'static str, open: Factory) -> Tab {
+            Tab {
+                name,
+                open: Some(open),
+            }
+        }
+    }
+
+    fn tabs() -> Vec<Tab> {
+        vec![
+            Tab::new("Missions", || {
+                Box::new(super::unimplemented_view::View::new("Missions"))
+            }),
+            Tab::new("Jobs", || Box::new(super::jobs_view::View::new())),
+            Tab::new("Rockets", || Box::new(super::rockets_view::View::new())),
+            Tab::new("Tick", || Box::new(super::tick_view::View::new())),
+            Tab::new("News", || Box::new(super::news_view::View::new())),
+            Tab::new("Legend", || Box::new(super::legend_view::View::new())),
+            Tab::new("Settings", || Box::new(super::settings_view::View::new())),
+            Tab {
+                name: "Exit",
+                open: None,
+            },
+        ]
+    }
+
+    /// A "go" command for each tab, which opens it as if it was picked from the menu.
+    pub fn commands() -> Vec<Command> {
+        let mut commands = vec![Command::anywhere("go menu", None, |_| {
+            Ok(Some(Transition::ResetTo(super::UI::home())))
+        })];
+        for tab in tabs() {
+            if let Some(open) = tab.open {
+                let name = format!("go {}", tab.name.to_lowercase());
+                commands.push(Command::anywhere(&name, None, move |_| {
+                    Ok(Some(Transition::Multiple(vec![
+                        Transition::ResetTo(super::UI::home()),
+                        Transition::Push(open()),
+                    ])))
+                }));
+            }
+        }
+        commands
+    }
+}
+
+mod unimplemented_view {
+    use super::view_prelude::*;
+    use crate::glyphs;
+    use termion::clear;
+
+    pub struct View {
+        title: &'static str,
+    }
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            ui_print!("{}", clear::All);
+            Label::new(format!("Unimplemended View. {}", glyphs::UNFINISHED.get()))
+                .draw(Rect::view().line(0));
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            match input {
+                Input::Back => Some(Transition::Pop),
+                _ => None,
+            }
+        }
+
+        fn title(&self) -> String {
+            self.title.to_string()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            vec![(Input::Back, "Go back")]
+        }
+    }
+
+    impl View {
+        pub fn new(title: &'static str) -> View {
+            View { title }
+        }
+    }
+}
+
+mod legend_view {
+    use super::view_prelude::*;
+    use crate::font_metrics;
+    use crate::rocket::Kind;
+    use termion::clear;
+
+    /// Lists the glyph each kind of component is drawn with.
+    pub struct View {
+        list: ScrollList,
+    }
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            ui_print!("{}", clear::All);
+            let width = Kind::ALL
+                .iter()
+                .map(|kind| font_metrics::width(kind.symbol().get()))
+                .max()
+                .unwrap_or(0);
+            self.list.draw(Rect::view(), Kind::ALL.len(), |idx, area| {
+                let kind = Kind::ALL[idx];
+                let cols = area.columns(&[Size::Fixed(width + 1), Size::Fill]);
+                Label::new(kind.symbol().get()).draw(cols[0]);
+                Label::new(kind.to_string()).draw(cols[1]);
+            });
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            match self.list.take_input(input, Kind::ALL.len(), View::label) {
+                Taken::Ignored => {}
+                Taken::Redraw => {
+                    self.full_redraw();
+                    return None;
+                }
+                Taken::Transition(trans) => {
+                    self.full_redraw();
+                    return Some(trans);
+                }
+            }
+
+            match input {
+                Input::Back => Some(Transition::Pop),
+                _ => None,
+            }
+        }
+
+        fn title(&self) -> String {
+            "Legend".to_string()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            let mut actions = self.list.actions();
+            actions.push((Input::Back, "Go back"));
+            actions
+        }
+    }
+
+    impl View {
+        pub fn new() -> View {
+            View {
+                list: ScrollList::new(1),
+            }
+        }
+
+        fn label(idx: usize) -> String {
+            Kind::ALL[idx].to_string()
+        }
+    }
+}
+
+mod jobs_view {
+    use super::view_prelude::*;
+    use crate::glyphs;
+    use termion::clear;
+
+    pub struct View {
+        list: ScrollList,
+        horiz_sel: HorizSel,
+        hits: HitMap<(usize, HorizSel)>,
+        /// The job waiting on the player to confirm declining it.
+        declining: Option<usize>,
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum HorizSel {
+        Name,
+        Accept,
+        Decline,
+    }
+    use HorizSel::*;
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            self.hits.clear();
+            ui_print!("{}", clear::All);
+            let jobs = GAME.available_jobs.lock().unwrap();
+            self.list.draw(Rect::view(), jobs.len(), |idx, area| {
+                self.hits.add(area, (idx, Name));
+                let job = &jobs[idx];
+                let focused = |sel| self.list.selected == idx && self.horiz_sel == sel;
+                let accept = Button::new(glyphs::ACCEPT.get()).focused(focused(Accept));
+                let decline = Button::new("X").focused(focused(Decline));
+                let cols = area.line(0).columns(&[
+                    Size::Fill,
+                    Size::Fixed(accept.width()),
+                    Size::Fixed(1),
+                    Size::Fixed(decline.width()),
+                ]);
+                Label::new(GAME.customers.on(job.customer, |c| c.name.clone()).unwrap())
+                    .draw(cols[0]);
+                accept.draw(cols[1]);
+                decline.draw(cols[3]);
+                Label::new(job.payload.to_string()).draw(area.line(1).indent(2));
+                self.hits.add(cols[1], (idx, Accept));
+                self.hits.add(cols[3], (idx, Decline));
+            });
+            drop(jobs);
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            let len = GAME.available_jobs.lock().unwrap().len();
+            match self.list.take_input(input, len, View::label) {
+                Taken::Ignored => {}
+                Taken::Redraw => {
+                    self.full_redraw();
+                    return None;
+                }
+                Taken::Transition(trans) => {
+                    self.full_redraw();
+                    return Some(trans);
+                }
+            }
+
+            match input {
+                Input::Back => Some(Transition::Pop),
+                Input::Left => {
+                    self.horiz_sel = match self.horiz_sel {
+                        Name | Accept => Name,
+                        Decline => Accept,
+                    };
+                    self.full_redraw();
+                    None
+                }
+                Input::Right => {
+                    self.horiz_sel = match self.horiz_sel {
+                        Name => Accept,
+                        Accept | Decline => Decline,
+                    };
+                    self.full_redraw();
+                    None
+                }
+                Input::Select => {
+                    if len != 0 {
+                        match self.horiz_sel {
+                            Name => {}
+                            Accept => GAME.accept_job_at(self.list.selected),
+                            Decline => {
+                                self.declining = Some(self.list.selected);
+                                let customer = View::customer(self.list.selected);
+                                return Some(Transition::Dialog(Box::new(Confirm::new(format!(
+                                    "Decline the job from {}?",
+                                    customer
+                                )))));
+                            }
+                        }
+                    }
+                    self.list.clamp(GAME.available_jobs.lock().unwrap().len());
+                    self.full_redraw();
+                    None
+                }
+                _ => None,
+            }
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
+        }
+
+        fn title(&self) -> String {
+            "Jobs".to_string()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            let mut actions = self.list.actions();
+            if !self.list.is_searching() {
+                actions.extend_from_slice(&[
+                    (Input::Left, "Previous button"),
+                    (Input::Right, "Next button"),
+                    (Input::Select, "Accept or decline the job"),
+                    (Input::Back, "Go back"),
+                ]);
+            }
+            actions
+        }
+
+        fn click(&mut self, target: usize) -> Option<Transition> {
+            let (selected, horiz_sel) = self.hits.get(target)?;
+            self.list.selected = selected;
+            self.horiz_sel = horiz_sel;
+            self.update(Input::Select)
+        }
+        fn dialog_result(&mut self, result: DialogResult) -> Option<Transition> {
+            if let (Some(idx), DialogResult::Confirmed(true)) = (self.declining.take(), result) {
+                GAME.decline_job_at(idx);
+                self.list.clamp(GAME.available_jobs.lock().unwrap().len());
+            }
+            None
+        }
+    }
+
+    impl View {
+        pub fn new() -> View {
+            View {
+                list: ScrollList::new(2).wrapping(),
+                horiz_sel: Name,
+                hits: HitMap::new(),
+                declining: None,
+            }
+        }
+
+        fn customer(idx: usize) -> String {
+            let customer = GAME.available_jobs.lock().unwrap()[idx].customer;
+            GAME.customers.on(customer, |c| c.name.clone()).unwrap()
+        }
+
+        fn label(idx: usize) -> String {
+            let payload = GAME.available_jobs.lock().unwrap()[idx].payload.to_string();
+            format!("{} {}", View::customer(idx), payload)
+        }
+    }
+}
+
+mod tick_view {
+    use super::view_prelude::*;
+    use crate::debug_log::DEBUG;
+    use crate::GAME;
+    use termion::clear;
+
+    pub struct View;
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            ui_print!("{}", clear::All);
+        }
+        fn update(&mut self, _: Input) -> Option<Transition> {
+            unreachable!()
+        }
+        fn start(&mut self) -> Option<Transition> {
+            GAME.tick();
+            DEBUG.log("Ticked");
+            Some(Transition::Pop)
+        }
+        fn title(&self) -> String {
+            "Tick".to_string()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            Vec::new()
+        }
+    }
+
+    impl View {
+        pub fn new() -> View {
+            View
+        }
+    }
+}
+
+mod news_view {
+    use super::view_prelude::*;
+    use termion::clear;
+
+    /// Every notice posted so far, newest first.
+    pub struct View {
+        list: ScrollList,
+    }
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            ui_print!("{}", clear::All);
+            self.list.draw(Rect::view(), GAME.news.len(), |idx, area| {
+                Label::new(View::label(idx)).draw(area);
+            });
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            match self.list.take_input(input, GAME.news.len(), View::label) {
+                Taken::Ignored => {}
+                Taken::Redraw => {
+                    self.full_redraw();
+                    return None;
+                }
+                Taken::Transition(trans) => {
+                    self.full_redraw();
+                    return Some(trans);
+                }
+            }
+
+            match input {
+                Input::Back => Some(Transition::Pop),
+                _ => None,
+            }
+        }
+
+        fn title(&self) -> String {
+            "News".to_string()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            let mut actions = self.list.actions();
+            actions.push((Input::Back, "Go back"));
+            actions
+        }
+    }
+
+    impl View {
+        pub fn new() -> View {
+            View {
+                list: ScrollList::new(1),
+            }
+        }
+
+        fn label(idx: usize) -> String {
+            let newest = GAME.news.len() - 1 - idx;
+            let notice = GAME.news.get(newest).unwrap();
+            format!("{}: {}", notice.topic.name(), notice.text)
+        }
+    }
+}
+
+mod rockets_view {
+    use super::rocket_builder_view::Saved;
+    use super::type_box::Validator;
+    use super::view_prelude::*;
+    use crate::blueprint;
+    use crate::keymap;
+    use crate::keymap::KEYMAP;
+    use crate::rocket::Rocket;
+    use std::any::Any;
+    use std::cmp::Reverse;
+    use std::path::Path;
+    use termion::clear;
+
+    pub struct View {
+        sel: Sel,
+        list: ScrollList,
+        hits: HitMap<(Sel, usize)>,
+        /// What the open dialog, if any, was asked for.
+        asked: Option<Question>,
+        /// The box the selected design is being renamed in.
+        renaming: Option<TypeBox>,
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Sel {
+        New,
+        Sort,
+        Compare,
+        Rocket,
+        RocketEdit,
+        RocketRename,
+    }
+
+    /// The buttons along the top, in order.
+    const HEADER: [Sel; 3] = [Sel::New, Sel::Sort, Sel::Compare];
+
+    /// The ids of the commands for blueprints, which follow those for sorting, and of the one
+    /// for comparing.
+    const EXPORT: usize = 2 + Order::ALL.len();
+    const IMPORT: usize = EXPORT + 1;
+    const COMPARE: usize = IMPORT + 1;
+    const PASTE: usize = COMPARE + 1;
+
+    #[derive(Clone, Copy)]
+    enum Question {
+        /// Whether to delete the design at the index.
+        Delete(usize),
+        Sort,
+        /// A blueprint to add as a design.
+        Paste,
+    }
+
+    /// What the designs can be sorted by.
+    #[derive(Clone, Copy)]
+    enum Order {
+        Name,
+        Mass,
+        /// Most room for payload first.
+        Payload,
+    }
+
+    impl Order {
+        const ALL: [Order; 3] = [Order::Name, Order::Mass, Order::Payload];
+
+        fn name(self) -> &'static str {
+            match self {
+                Order::Name => "Name",
+                Order::Mass => "Mass",
+                Order::Payload => "Payload",
+            }
+        }
+    }
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            if let Some(name) = &self.renaming {
+                name.before_render();
+            }
+            self.hits.clear();
+            ui_print!("{}", clear::All);
+            let parts = Rect::view().rows(&[Size::Fixed(1), Size::Fill]);
+
+            let new = Button::new("+").focused(self.sel == Sel::New);
+            let sort = Button::new("sort").focused(self.sel == Sel::Sort);
+            let compare = Button::new("compare").focused(self.sel == Sel::Compare);
+            let header = parts[0].columns(&[
+                Size::Fixed(8),
+                Size::Fixed(new.width()),
+                Size::Fixed(1),
+                Size::Fixed(sort.width()),
+                Size::Fixed(1),
+                Size::Fixed(compare.width()),
+            ]);
+            Label::new("Rockets").draw(header[0]);
+            new.draw(header[1]);
+            sort.draw(header[3]);
+            compare.draw(header[5]);
+            self.hits.add(header[1], (Sel::New, 0));
+            self.hits.add(header[3], (Sel::Sort, 0));
+            self.hits.add(header[5], (Sel::Compare, 0));
+
+            let rockets = GAME.rocket_designs.lock().unwrap();
+            self.list.draw(parts[1], rockets.len(), |idx, area| {
+                self.hits.add(area.line(0), (Sel::Rocket, idx));
+                let edit = Button::new("edit").focused(self.sel == Sel::RocketEdit);
+                let rename = Button::new("rename").focused(self.sel == Sel::RocketRename);
+                let cols = area.line(0).columns(&[
+                    Size::Fill,
+                    Size::Fixed(1),
+                    Size::Fixed(edit.width()),
+                    Size::Fixed(1),
+                    Size::Fixed(rename.width()),
+                ]);
+                let rocket = &rockets[idx];
+                let selected = !self.in_header() && self.list.selected == idx;
+                match &self.renaming {
+                    Some(name) if selected => {
+                        name.draw(cols[0]);
+                        if name.error().is_some() {
+                            name.draw_error(area.line(1));
+                        }
+                    }
+                    _ => Label::new(format!("{}: {}", rocket.name, rocket)).draw(cols[0]),
+                }
+                if selected {
+                    edit.draw(cols[2]);
+                    rename.draw(cols[4]);
+                    self.hits.add(cols[2], (Sel::RocketEdit, idx));
+                    self.hits.add(cols[4], (Sel::RocketRename, idx));
+                }
+            });
+            drop(rockets);
+
+            if let Some(name) = &self.renaming {
+                name.after_render();
+            }
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            if self.renaming.is_some() {
+                return self.rename_input(input);
+            }
+
+            let rocket_cnt = GAME.rocket_designs.lock().unwrap().len();
+            if rocket_cnt == 0 && !self.in_header() {
+                self.set_sel(Sel::New);
+            }
+
+            match (self.sel, input) {
+                _ if self.list.is_searching() => {}
+                (_, Input::Up) if self.in_header() && rocket_cnt != 0 => {
+                    self.set_sel(Sel::Rocket);
+                    self.list.selected = rocket_cnt - 1;
+                    self.full_redraw();
+                    return None;
+                }
+                (_, Input::Down) if self.in_header() && rocket_cnt != 0 => {
+                    self.set_sel(Sel::Rocket);
+                    self.list.selected = 0;
+                    self.full_redraw();
+                    return None;
+                }
+                (_, Input::Up) if !self.in_header() && self.list.selected == 0 => {
+                    self.set_sel(Sel::New);
+                    self.full_redraw();
+                    return None;
+                }
+                (_, Input::Down) if !self.in_header() && self.list.selected + 1 == rocket_cnt => {
+                    self.set_sel(Sel::New);
+                    self.full_redraw();
+                    return None;
+                }
+                _ => {}
+            }
+
+            match self.list.take_input(input, rocket_cnt, View::label) {
+                Taken::Ignored => {}
+                Taken::Redraw => {
+                    self.set_sel(Sel::Rocket);
+                    self.full_redraw();
+                    return None;
+                }
+                Taken::Transition(trans) => {
+                    self.set_sel(Sel::Rocket);
+                    self.full_redraw();
+                    return Some(trans);
+                }
+            }
+
+            match input {
+                Input::Back => Some(Transition::Pop),
+                Input::Left | Input::Right => {
+                    let forward = input == Input::Right;
+                    let len = HEADER.len();
+                    let header = HEADER.iter().position(|&sel| sel == self.sel);
+                    self.set_sel(match (self.sel, header) {
+                        (_, Some(idx)) if forward => HEADER[(idx + 1) % len],
+                        (_, Some(idx)) => HEADER[(idx + len - 1) % len],
+                        //Either way from the design goes to its nearest button
+                        (Sel::Rocket, _) => Sel::RocketEdit,
+                        (Sel::RocketEdit, _) if forward => Sel::RocketRename,
+                        (Sel::RocketRename, _) if !forward => Sel::RocketEdit,
+                        _ => Sel::Rocket,
+                    });
+                    self.full_redraw();
+                    None
+                }
+                Input::Select => match self.sel {
+                    Sel::New => Some(Transition::Push(Box::new(
+                        super::rocket_builder_view::View::new_rocket(),
+                    ))),
+                    Sel::Sort => {
+                        self.asked = Some(Question::Sort);
+                        let orders = Order::ALL.iter().map(|o| o.name().to_string()).collect();
+                        Some(Transition::Dialog(Box::new(Choice::new("Sort by", orders))))
+                    }
+                    Sel::Compare => Some(
+                        compare().unwrap_or_else(|e| Transition::Dialog(Box::new(Alert::new(e)))),
+                    ),
+                    Sel::RocketEdit => Some(Transition::Push(Box::new(
+                        super::rocket_builder_view::View::edit_rocket(self.list.selected),
+                    ))),
+                    Sel::RocketRename => Some(self.start_renaming()),
+                    Sel::Rocket => Some(Transition::Push(Box::new(
+                        super::rocket_detail_view::View::new(self.list.selected),
+                    ))),
+                },
+                Input::Del if !self.in_header() => {
+                    self.asked = Some(Question::Delete(self.list.selected));
+                    Some(super::rocket_detail_view::confirm_delete(
+                        self.list.selected,
+                    ))
+                }
+                Input::Duplicate if !self.in_header() => {
+                    self.list.selected = duplicate(self.list.selected);
+                    self.full_redraw();
+                    None
+                }
+                _ => None,
+            }
+        }
+
+        fn start(&mut self) -> Option<Transition> {
+            self.check_idx();
+            self.full_redraw();
+            None
+        }
+
+        fn restart(&mut self, returned: Option<Box<dyn Any>>) -> Option<Transition> {
+            //Show the design that was just saved
+            if let Some(Saved(idx)) = returned.and_then(|r| r.downcast().ok()).map(|s| *s) {
+                self.set_sel(Sel::Rocket);
+                self.list.selected = idx;
+            }
+            self.check_idx();
+            self.full_redraw();
+            None
+        }
+
+        fn title(&self) -> String {
+            "Rockets".to_string()
+        }
+
+        fn actions(&self, mode: InputMode) -> Vec<Action> {
+            if let Some(name) = &self.renaming {
+                let mut actions = name.actions();
+                actions.extend_from_slice(&[
+                    (Input::Type('\n'), "Finish renaming"),
+                    (Input::Back, "Keep the old name"),
+                ]);
+                return actions;
+            }
+            let mut actions = self.list.actions();
+            if !self.list.is_searching() && mode == InputMode::Control {
+                actions.extend_from_slice(&[
+                    (Input::Left, "Move along the design's buttons"),
+                    (Input::Right, "Move along the design's buttons"),
+                    (Input::Select, "Open"),
+                    (Input::Del, "Delete the design"),
+                    (Input::Duplicate, "Copy the design as a new one"),
+                    (Input::Back, "Go back"),
+                ]);
+            }
+            actions
+        }
+
+        fn marker(&self) -> Option<Marker> {
+            Some(Marker::Rockets)
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
+        }
+
+        fn click(&mut self, target: usize) -> Option<Transition> {
+            let (sel, idx) = self.hits.get(target)?;
+            let mut transitions = Vec::new();
+            if self.renaming.is_some() {
+                if sel == Sel::Rocket && idx == self.list.selected {
+                    return None;
+                }
+                transitions.extend(self.update(Input::Type('\n')));
+                if self.renaming.is_some() {
+                    //The name was refused, so the box stays to show why
+                    return Some(Transition::Multiple(transitions));
+                }
+            }
+            self.set_sel(sel);
+            self.list.selected = idx;
+            let trans = match self.sel {
+                Sel::New | Sel::Sort | Sel::Compare | Sel::RocketEdit | Sel::RocketRename => {
+                    self.update(Input::Select)
+                }
+                Sel::Rocket => {
+                    self.full_redraw();
+                    None
+                }
+            };
+            transitions.extend(trans);
+            Some(Transition::Multiple(transitions))
+        }
+
+        fn dialog_result(&mut self, result: DialogResult) -> Option<Transition> {
+            match (self.asked.take()?, result) {
+                (Question::Delete(idx), DialogResult::Confirmed(true)) => {
+                    GAME.rocket_designs.lock().unwrap().remove(idx);
+                    self.check_idx();
+                }
+                (Question::Sort, DialogResult::Picked(Some(order))) => {
+                    self.sort(Order::ALL[order]);
+                }
+                (Question::Paste, DialogResult::Text(Some(text))) => {
+                    if let Err(e) = paste(&text) {
+                        return Some(Transition::Dialog(Box::new(Alert::new(e))));
+                    }
+                    self.set_sel(Sel::Rocket);
+                    self.list.selected = GAME.rocket_designs.lock().unwrap().len() - 1;
+                }
+                _ => {}
+            }
+            None
+        }
+
+        fn commands(&self) -> Vec<Command> {
+            let mut commands = vec![
+                Command::in_view("rename", None, 0),
+                Command::in_view("duplicate", None, 1),
+            ];
+            for (idx, order) in Order::ALL.iter().enumerate() {
+                let name = format!("sort by {}", order.name().to_lowercase());
+                commands.push(Command::in_view(&name, None, 2 + idx));
+            }
+            commands.push(Command::in_view("export blueprint", None, EXPORT));
+            commands.push(Command::in_view("import blueprints", None, IMPORT));
+            commands.push(Command::in_view("compare designs", None, COMPARE));
+            commands.push(Command::in_view("paste blueprint", None, PASTE));
+            commands
+        }
+
+        fn run_command(&mut self, id: usize, _: Option<u32>) -> Ran {
+            if id == COMPARE {
+                return compare().map(Some);
+            }
+            if id == PASTE {
+                self.asked = Some(Question::Paste);
+                let text = TypeBox::new()
+                    .with_width(30)
+                    .multi_line(8)
+                    .validate(Validator::NonEmpty);
+                //Enter starts a new line, so the prompt says which key adds the design
+                let keys = KEYMAP
+                    .lock()
+                    .unwrap()
+                    .keys_for(InputMode::Type, Input::Select);
+                let keys: Vec<String> = keys.into_iter().map(keymap::key_name).collect();
+                let message = format!("Blueprint, {} to add:", keys.join(" or "));
+                return Ok(Some(Transition::Dialog(Box::new(Prompt::new(
+                    message, text,
+                )))));
+            }
+            if id == IMPORT {
+                let dir = blueprint::dir()
+                    .ok_or_else(|| "There is no config directory to import from".to_string())?;
+                let imported = import(&dir)?;
+                self.check_idx();
+                self.full_redraw();
+                return Ok(Some(Transition::Dialog(Box::new(Alert::new(imported)))));
+            }
+            if GAME.rocket_designs.lock().unwrap().is_empty() {
+                return Err("There are no designs".to_string());
+            }
+            if self.in_header() {
+                self.set_sel(Sel::Rocket);
+            }
+            let trans = match id {
+                0 => Some(self.start_renaming()),
+                1 => {
+                    self.list.selected = duplicate(self.list.selected);
+                    None
+                }
+                EXPORT => {
+                    let rocket = GAME.rocket_designs.lock().unwrap()[self.list.selected].clone();
+                    let dir = blueprint::dir()
+                        .ok_or_else(|| "There is no config directory to export to".to_string())?;
+                    let path = blueprint::export(&dir, &rocket)
+                        .map_err(|e| format!("Could not export {}: {}", rocket.name, e))?;
+                    let file = path.file_name().unwrap_or_default().to_string_lossy();
+                    let exported = format!("Exported {} to\n{}", file, blueprint::shown(&dir));
+                    Some(Transition::Dialog(Box::new(Alert::new(exported))))
+                }
+                _ => {
+                    self.sort(Order::ALL[id - 2]);
+                    None
+                }
+            };
+            self.full_redraw();
+            Ok(trans)
+        }
+    }
+
+    impl View {
+        pub fn new() -> View {
+            View {
+                sel: Sel::Rocket,
+                list: ScrollList::new(2),
+                hits: HitMap::new(),
+                asked: None,
+                renaming: None,
+            }
+        }
+
+        fn check_idx(&mut self) {
+            let rocket_cnt = GAME.rocket_designs.lock().unwrap().len();
+            if rocket_cnt == 0 {
+                self.set_sel(Sel::New);
+            }
+            self.list.clamp(rocket_cnt);
+        }
+
+        /// Moves the selection, showing the list's marker only while a rocket is selected.
+        fn set_sel(&mut self, sel: Sel) {
+            self.sel = sel;
+            self.list.focused = !self.in_header();
+        }
+
+        fn in_header(&self) -> bool {
+            HEADER.contains(&self.sel)
+        }
+
+        /// Swaps the selected design's name for a box to type a new one in.
+        fn start_renaming(&mut self) -> Transition {
+            let idx = self.list.selected;
+            let mut name = super::rocket_builder_view::name_box(Some(idx));
+            name.set_content(GAME.rocket_designs.lock().unwrap()[idx].name.as_str());
+            name.activate(true);
+            self.renaming = Some(name);
+            self.full_redraw();
+            Transition::InputMode(InputMode::Type)
+        }
+
+        fn rename_input(&mut self, input: Input) -> Option<Transition> {
+            let name = self.renaming.as_mut()?;
+            match input {
+                Input::Back => {
+                    name.activate(false);
+                    self.renaming = None;
+                }
+                Input::Type('\n') => match name.submit() {
+                    Some(new_name) => {
+                        name.activate(false);
+                        GAME.rocket_designs.lock().unwrap()[self.list.selected].name = new_name;
+                        self.renaming = None;
+                    }
+                    None => {
+                        self.full_redraw();
+                        return None;
+                    }
+                },
+                _ => {
+                    if name.take_input(&input) {
+                        self.full_redraw();
+                    }
+                    return None;
+                }
+            }
+            self.full_redraw();
+            Some(Transition::InputMode(InputMode::Control))
+        }
+
+        /// Reorders the designs, keeping the selected one selected.
+        fn sort(&mut self, order: Order) {
+            let mut designs = GAME.rocket_designs.lock().unwrap();
+            let mut sorted: Vec<usize> = (0..designs.len()).collect();
+            match order {
+                Order::Name => sorted.sort_by_key(|&idx| designs[idx].name.to_lowercase()),
+                Order::Mass => sorted.sort_by_key(|&idx| designs[idx].mass()),
+                Order::Payload => sorted.sort_by_key(|&idx| Reverse(designs[idx].payload_volume())),
+            }
+            *designs = sorted.iter().map(|&idx| designs[idx].clone()).collect();
+            self.list.selected = sorted
+                .iter()
+                .position(|&idx| idx == self.list.selected)
+                .unwrap_or(0);
+        }
+
+        fn label(idx: usize) -> String {
+            GAME.rocket_designs.lock().unwrap()[idx].name.clone()
+        }
+    }
+
+    /// Opens the comparison of every design, if there are enough to compare.
+    fn compare() -> Result<Transition, String> {
+        if GAME.rocket_designs.lock().unwrap().len() < 2 {
+            return Err("Comparing needs at least two designs".to_string());
+        }
+        Ok(Transition::Push(Box::new(super::compare_view::View::new())))
+    }
+
+    /// Adds every blueprint in `dir` as a design, returning what to tell the player about how
+    /// it went.
+    pub fn import(dir: &Path) -> Result<String, String> {
+        let known = GAME.known_components.lock().unwrap().clone();
+        let read = match blueprint::import_all(dir, &known) {
+            Ok(read) if !read.is_empty() => read,
+            Ok(_) => {
+                return Err(format!(
+                    "There are no blueprints in\n{}",
+                    blueprint::shown(dir)
+                ))
+            }
+            Err(e) => return Err(format!("Could not read\n{}\n{}", blueprint::shown(dir), e)),
+        };
+        let mut designs = GAME.rocket_designs.lock().unwrap();
+        let mut problems = Vec::new();
+        let total = read.len();
+        for (file, rocket) in read {
+            match rocket {
+                Ok(rocket)
+                    if designs
+                        .iter()
+                        .any(|r| r.name.to_lowercase() == rocket.name.to_lowercase()) =>
+                {
+                    problems.push(format!("{}, {} is already a design", file, rocket.name));
+                }
+                Ok(rocket) => designs.push(rocket),
+                Err(e) => problems.push(format!("{}, {}", file, e)),
+            }
+        }
+        let imported = total - problems.len();
+        problems.insert(0, format!("Imported {} of {} blueprints", imported, total));
+        Ok(problems.join("\n"))
+    }
+
+    /// Adds the design in a blueprint typed or pasted in by the player.
+    fn paste(text: &str) -> Result<(), String> {
+        let known = GAME.known_components.lock().unwrap().clone();
+        let rocket =
+            blueprint::parse(text, &known).map_err(|e| format!("Can not add it:\n{}", e))?;
+        let mut designs = GAME.rocket_designs.lock().unwrap();
+        if designs
+            .iter()
+            .any(|r| r.name.to_lowercase() == rocket.name.to_lowercase())
+        {
+            return Err(format!(
+                "Can not add it:\n{} is already a design",
+                rocket.name
+            ));
+        }
+        designs.push(rocket);
+        Ok(())
+    }
+
+    /// Copies the design at `idx` as a new one just after it, returning where the copy is.
+    pub fn duplicate(idx: usize) -> usize {
+        let mut designs = GAME.rocket_designs.lock().unwrap();
+        let mut copy = designs[idx].clone();
+        copy.name = free_name(&designs, &format!("{} copy", copy.name));
+        designs.insert(idx + 1, copy);
+        idx + 1
+    }
+
+    /// `name`, or if a design already has it, `name` followed by the lowest number from 2 that
+    /// makes it one no design has.
+    pub fn free_name(designs: &[Rocket], name: &str) -> String {
+        let taken = |name: &str| designs.iter().any(|rocket| rocket.name == name);
+        let mut free = name.to_string();
+        let mut number = 2;
+        while taken(&free) {
+            free = format!("{} {}", name, number);
+            number += 1;
+        }
+        free
+    }
+
+    pub fn commands() -> Vec<Command> {
+        vec![Command::anywhere("new rocket", None, |_| {
+            Ok(Some(Transition::Multiple(vec![
+                Transition::ResetTo(super::UI::home()),
+                Transition::Push(Box::new(View::new())),
+                Transition::Push(Box::new(super::rocket_builder_view::View::new_rocket())),
+            ])))
+        })]
+    }
+}
+
+mod rocket_detail_view {
+    use super::type_box::Validator;
+    use super::view_prelude::*;
+    use crate::units::Dimension;
+    use crate::units::Quantity;
+    use termion::clear;
+
+    /// A saved design, drawn upright with its figures beside it.
+    pub struct View {
+        idx: usize,
+        /// What the open dialog, if any, was asked for.
+        asked: Option<Question>,
+    }
+
+    #[derive(Clone, Copy)]
+    enum Question {
+        Action,
+        Rename,
+        Delete,
+        Move,
+        Fit,
+    }
+
+    const ACTIONS: [&str; 6] = ["Edit", "Rename", "Duplicate", "Delete", "Move", "Check fit"];
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            ui_print!("{}", clear::All);
+            let rocket = GAME.rocket_designs.lock().unwrap()[self.idx].clone();
+            let diagram = RocketDiagram::new(&rocket);
+            let cols = Rect::view().columns(&[Size::Fill, Size::Fixed(diagram.width() + 2)]);
+            diagram.draw(cols[1].indent(2));
+
+            let parts = cols[0].rows(&[Size::Fixed(3), Size::Fill]);
+            Label::new(format!("Mass: {}", rocket.mass())).draw(parts[0].line(0));
+            Label::new(format!("Stages: {}", rocket.stage_count())).draw(parts[0].line(1));
+            Label::new(format!("Payload: {}", rocket.payload_volume())).draw(parts[0].line(2));
+            for (row, component) in (0..parts[1].height).zip(&rocket.components) {
+                Label::new(format!(
+                    "{} {} ({})",
+                    component,
+                    component.name,
+                    component.class.kind()
+                ))
+                .draw(parts[1].line(row));
+            }
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            match input {
+                Input::Back => Some(Transition::Pop),
+                Input::Select => {
+                    self.asked = Some(Question::Action);
+                    let name = GAME.rocket_designs.lock().unwrap()[self.idx].name.clone();
+                    let actions = ACTIONS.iter().map(|a| a.to_string()).collect();
+                    Some(Transition::Dialog(Box::new(Choice::new(name, actions))))
+                }
+                Input::Del => {
+                    self.asked = Some(Question::Delete);
+                    Some(confirm_delete(self.idx))
+                }
+                Input::Duplicate => Some(self.duplicate()),
+                _ => None,
+            }
+        }
+
+        fn title(&self) -> String {
+            GAME.rocket_designs.lock().unwrap()[self.idx].name.clone()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            vec![
+                (Input::Select, "Choose what to do with the design"),
+                (Input::Del, "Delete the design"),
+                (Input::Duplicate, "Copy the design as a new one"),
+                (Input::Back, "Go back"),
+            ]
+        }
+
+        fn commands(&self) -> Vec<Command> {
+            ACTIONS
+                .iter()
+                .enumerate()
+                .map(|(id, action)| Command::in_view(&action.to_lowercase(), None, id))
+                .collect()
+        }
+
+        fn run_command(&mut self, id: usize, _: Option<u32>) -> Ran {
+            //The same as picking the action from the list
+            self.asked = Some(Question::Action);
+            Ok(self.dialog_result(DialogResult::Picked(Some(id))))
+        }
+
+        fn dialog_result(&mut self, result: DialogResult) -> Option<Transition> {
+            match (self.asked.take()?, result) {
+                (Question::Action, DialogResult::Picked(Some(0))) => Some(Transition::Push(
+                    Box::new(super::rocket_builder_view::View::edit_rocket(self.idx)),
+                )),
+                (Question::Action, DialogResult::Picked(Some(1))) => {
+                    self.asked = Some(Question::Rename);
+                    let name = GAME.rocket_designs.lock().unwrap()[self.idx].name.clone();
+                    let mut text = super::rocket_builder_view::name_box(Some(self.idx));
+                    text.set_content(name);
+                    Some(Transition::Dialog(Box::new(Prompt::new(
+                        "Rename to:",
+                        text,
+                    ))))
+                }
+                (Question::Action, DialogResult::Picked(Some(2))) => Some(self.duplicate()),
+                (Question::Action, DialogResult::Picked(Some(3))) => {
+                    self.asked = Some(Question::Delete);
+                    Some(confirm_delete(self.idx))
+                }
+                (Question::Action, DialogResult::Picked(Some(4))) => {
+                    self.asked = Some(Question::Move);
+                    let mut text = TypeBox::new().with_width(4).validate(Validator::Numeric);
+                    text.set_content((self.idx + 1).to_string());
+                    Some(Transition::Dialog(Box::new(Prompt::new(
+                        "Move to position:",
+                        text,
+                    ))))
+                }
+                (Question::Action, DialogResult::Picked(Some(5))) => {
+                    self.asked = Some(Question::Fit);
+                    let text = TypeBox::new()
+                        .with_width(12)
+                        .validate(Validator::Quantity(Dimension::Volume));
+                    Some(Transition::Dialog(Box::new(Prompt::new(
+                        "Payload volume:",
+                        text,
+                    ))))
+                }
+                (Question::Rename, DialogResult::Text(Some(name))) => {
+                    GAME.rocket_designs.lock().unwrap()[self.idx].name = name;
+                    None
+                }
+                (Question::Delete, DialogResult::Confirmed(true)) => {
+                    GAME.rocket_designs.lock().unwrap().remove(self.idx);
+                    Some(Transition::PopTo(Marker::Rockets))
+                }
+                (Question::Move, DialogResult::Text(Some(place))) => {
+                    let mut designs = GAME.rocket_designs.lock().unwrap();
+                    let place = place.trim().parse::<usize>().unwrap_or(0);
+                    let rocket = designs.remove(self.idx);
+                    self.idx = place.clamp(1, designs.len() + 1) - 1;
+                    designs.insert(self.idx, rocket);
+                    None
+                }
+                (Question::Fit, DialogResult::Text(Some(payload))) => {
+                    let payload = match Quantity::parse(&payload)? {
+                        Quantity::Volume(payload) => payload,
+                        Quantity::Mass(_) => return None,
+                    };
+                    let room = GAME.rocket_designs.lock().unwrap()[self.idx].payload_volume();
+                    let message = if payload <= room {
+                        format!("It fits, with {} to spare", room - payload)
+                    } else {
+                        format!("It needs {} more room", payload - room)
+                    };
+                    Some(Transition::Dialog(Box::new(Alert::new(message))))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    impl View {
+        pub fn new(idx: usize) -> View {
+            View { idx, asked: None }
+        }
+
+        /// Copies the design, showing the copy in place of it.
+        fn duplicate(&self) -> Transition {
+            let idx = super::rockets_view::duplicate(self.idx);
+            Transition::Replace(Box::new(View::new(idx)))
+        }
+    }
+
+    /// Asks whether to delete the design at `idx`.
+    pub fn confirm_delete(idx: usize) -> Transition {
+        let name = GAME.rocket_designs.lock().unwrap()[idx].name.clone();
+        Transition::Dialog(Box::new(Confirm::new(format!("Delete {}?", name))))
+    }
+}
+
+mod compare_view {
+    use super::view_prelude::*;
+    use crate::font_metrics;
+    use crate::rocket::Rocket;
+    use crate::units::Speed;
+    use std::any::Any;
+    use termion::clear;
+    use termion::style;
+
+    /// Saved designs side by side, with the best of each figure picked out.
+    pub struct View {
+        /// The designs being compared, by where they are in the list of designs.
+        shown: Vec<usize>,
+        /// Which of `shown` is selected.
+        sel: usize,
+        /// How many designs there were when the comparison started, to notice that changing.
+        designs: usize,
+        hits: HitMap<usize>,
+    }
+
+    /// What the designs are compared by.
+    #[derive(Clone, Copy)]
+    enum Figure {
+        Mass,
+        /// The delta-v of every stage together.
+        DeltaV,
+        /// The delta-v of the stage that fires after this many others.
+        StageDeltaV(usize),
+        FairingVolume,
+    }
+
+    impl Figure {
+        /// The figures to compare `rockets` by, with a row for each stage of the one with the
+        /// most.
+        fn all(rockets: &[&Rocket]) -> Vec<Figure> {
+            let stages = rockets.iter().map(|r| r.stage_count()).max().unwrap_or(0);
+            let mut figures = vec![Figure::Mass, Figure::DeltaV];
+            figures.extend((0..stages).map(Figure::StageDeltaV));
+            figures.push(Figure::FairingVolume);
+            figures
+        }
+
+        fn name(self) -> String {
+            match self {
+                Figure::Mass => "Mass".to_string(),
+                Figure::DeltaV => "Delta-v".to_string(),
+                Figure::StageDeltaV(stage) => format!("  Stage {}", stage + 1),
+                Figure::FairingVolume => "Fairing volume".to_string(),
+            }
+        }
+
+        fn delta_v(rocket: &Rocket) -> Speed {
+            let stages = rocket.stage_delta_v().into_iter();
+            stages.fold(Speed::m_per_s(0.0), |total, stage| total + stage)
+        }
+
+        fn show(self, rocket: &Rocket) -> String {
+            match self {
+                Figure::Mass => rocket.mass().to_string(),
+                Figure::DeltaV => Figure::delta_v(rocket).to_string(),
+                Figure::StageDeltaV(stage) => match rocket.stage_delta_v().get(stage) {
+                    Some(delta_v) => delta_v.to_string(),
+                    None => "-".to_string(),
+                },
+                Figure::FairingVolume => rocket.payload_volume().to_string(),
+            }
+        }
+
+        /// How good the figure is, higher being better, if the rocket has it at all.
+        fn score(self, rocket: &Rocket) -> Option<f64> {
+            match self {
+                Figure::Mass => Some(-rocket.mass().in_kg()),
+                Figure::DeltaV => Some(Figure::delta_v(rocket).in_m_per_s()),
+                Figure::StageDeltaV(stage) => rocket
+                    .stage_delta_v()
+                    .get(stage)
+                    .map(|delta_v| delta_v.in_m_per_s()),
+                Figure::FairingVolume => Some(rocket.payload_volume().in_m3()),
+            }
+        }
+
+        /// Which of `rockets` are best by this figure, with none picked out if all those that
+        /// have it are alike.
+        fn best(self, rockets: &[&Rocket]) -> Vec<bool> {
+            let scores: Vec<Option<f64>> = rockets.iter().map(|r| self.score(r)).collect();
+            let top = scores.iter().flatten().copied().fold(f64::MIN, f64::max);
+            let alike = scores.iter().flatten().all(|&score| score == top);
+            scores
+                .iter()
+                .map(|&score| !alike && score == Some(top))
+                .collect()
+        }
+    }
+
+    /// The width of the column naming each figure.
+    const LABEL_WIDTH: u16 = 14;
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            self.hits.clear();
+            ui_print!("{}", clear::All);
+            let designs = GAME.rocket_designs.lock().unwrap();
+            let rockets: Vec<&Rocket> = self.shown.iter().map(|&idx| &designs[idx]).collect();
+
+            let figures = Figure::all(&rockets);
+            let values: Vec<Vec<String>> = figures
+                .iter()
+                .map(|figure| rockets.iter().map(|r| figure.show(r)).collect())
+                .collect();
+            let width = rockets
+                .iter()
+                .map(|r| {
+                    let parts = font_metrics::width(&r.to_string()) + 1;
+                    Button::new(r.name.as_str()).width().max(parts)
+                })
+                .chain(values.iter().flatten().map(|v| font_metrics::width(v)))
+                .max()
+                .unwrap_or(0)
+                + 2;
+
+            //The selected design stays in view, with as many before it as fit
+            let area = Rect::view();
+            let fits = ((area.width.saturating_sub(LABEL_WIDTH)) / width).max(1) as usize;
+            let first = self.sel.saturating_sub(fits - 1);
+            let shown = first..rockets.len().min(first + fits);
+
+            let mut sizes = vec![Size::Fixed(LABEL_WIDTH)];
+            sizes.extend(shown.clone().map(|_| Size::Fixed(width)));
+            let rows = area.rows(&[Size::Fixed(2), Size::Fixed(1), Size::Fill]);
+            let header = rows[0].columns(&sizes);
+            for (col, idx) in header[1..].iter().zip(shown.clone()) {
+                Button::new(rockets[idx].name.as_str())
+                    .focused(idx == self.sel)
+                    .draw(col.line(0));
+                Label::new(format!(" {}", rockets[idx])).draw(col.line(1));
+                self.hits.add(*col, idx);
+            }
+
+            for (row, (figure, values)) in figures.iter().zip(&values).enumerate() {
+                let cols = rows[2].line(row as u16).columns(&sizes);
+                Label::new(figure.name()).draw(cols[0]);
+                let best = figure.best(&rockets);
+                for (col, idx) in cols[1..].iter().zip(shown.clone()) {
+                    let value = Label::new(values[idx].as_str());
+                    let area = col.indent(1);
+                    if best[idx] {
+                        ui_print!("{}", style::Invert);
+                        value.draw(area);
+                        ui_print!("{}", style::NoInvert);
+                    } else {
+                        value.draw(area);
+                    }
+                }
+            }
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            let len = self.shown.len();
+            match input {
+                Input::Back => return Some(Transition::Pop),
+                Input::Left => self.sel = (self.sel + len - 1) % len,
+                Input::Right => self.sel = (self.sel + 1) % len,
+                //Two are left at least, or there would be nothing to compare
+                Input::Del if len > 2 => {
+                    self.shown.remove(self.sel);
+                    self.sel = self.sel.min(len - 2);
+                }
+                Input::Select => {
+                    return Some(Transition::Push(Box::new(
+                        super::rocket_detail_view::View::new(self.shown[self.sel]),
+                    )))
+                }
+                _ => return None,
+            }
+            self.full_redraw();
+            None
+        }
+
+        fn restart(&mut self, _: Option<Box<dyn Any>>) -> Option<Transition> {
+            //Designs were added or removed, so the indices no longer hold
+            let designs = GAME.rocket_designs.lock().unwrap().len();
+            if designs != self.designs {
+                if designs < 2 {
+                    return Some(Transition::Pop);
+                }
+                *self = View::new();
+            }
+            self.full_redraw();
+            None
+        }
+
+        fn title(&self) -> String {
+            "Compare".to_string()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            vec![
+                (Input::Left, "Previous design"),
+                (Input::Right, "Next design"),
+                (Input::Select, "Open the design"),
+                (Input::Del, "Leave the design out"),
+                (Input::Back, "Go back"),
+            ]
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
+        }
+
+        fn click(&mut self, target: usize) -> Option<Transition> {
+            self.sel = self.hits.get(target)?;
+            self.full_redraw();
+            None
+        }
+    }
+
+    impl View {
+        /// Compares every saved design.
+        pub fn new() -> View {
+            let designs = GAME.rocket_designs.lock().unwrap().len();
+            View {
+                shown: (0..designs).collect(),
+                sel: 0,
+                designs,
+                hits: HitMap::new(),
+            }
+        }
+    }
+}
+
+mod rocket_builder_view {
+    use super::type_box::{History, Validator};
+    use super::view_prelude::*;
+    use crate::rocket::Component;
+    use crate::rocket::Rocket;
+    use std::ops::Range;
+    use std::sync::Mutex;
+    use termion::clear;
+
+    pub struct View {
+        edited: Edited,
+        rocket: Rocket,
+        sel: Sel,
+        name: TypeBox,
+        catalog: ScrollList,
+        hits: HitMap<(Sel, usize)>,
+        undo: Vec<Edit>,
+        redo: Vec<Edit>,
+        /// How many edits in the saved design is, if undoing or redoing can still get back to it.
+        saved_at: Option<usize>,
+        /// The other end, from the selected component, of the components marked to act on
+        /// together.
+        mark: Option<usize>,
+        /// Where components picked from the catalog go, if not on the end.
+        insert_at: Option<usize>,
+        /// Whether the UI is holding back a transition until the unsaved changes are saved or
+        /// discarded.
+        held_back: bool,
+        /// Whether the unsaved changes were discarded, so the builder can be left without asking.
+        discarded: bool,
+    }
+
+    #[derive(Clone, Copy)]
+    enum Sel {
+        RocketComponent(usize),
+        /// The selected entry in the catalog of known components.
+        NewComponent,
+        Save,
+        Name,
+    }
+
+    enum Edited {
+        New,
+        Edit(usize),
+    }
+
+    /// A change to the rocket, kept so it can be undone.
+    enum Edit {
+        /// Components put in, the first at the index.
+        Insert(usize, Vec<Component>),
+        /// Components taken out, the first from the index.
+        Remove(usize, Vec<Component>),
+        /// Components moved along, keeping their order.
+        Move {
+            from: usize,
+            to: usize,
+            len: usize,
+        },
+        Rename {
+            from: String,
+            to: String,
+        },
+        /// The whole rocket swapped for another, as reverting to the saved design does.
+        Replace {
+            from: Rocket,
+            to: Rocket,
+        },
+    }
+
+    impl Edit {
+        fn apply(&self, rocket: &mut Rocket) {
+            match self {
+                Edit::Insert(at, components) => {
+                    rocket
+                        .components
+                        .splice(*at..*at, components.iter().cloned());
+                }
+                Edit::Remove(at, components) => {
+                    rocket.components.drain(*at..*at + components.len());
+                }
+                &Edit::Move { from, to, len } => {
+                    let moved: Vec<Component> = rocket.components.drain(from..from + len).collect();
+                    rocket.components.splice(to..to, moved);
+                }
+                Edit::Rename { to, .. } => rocket.name = to.clone(),
+                Edit::Replace { to, .. } => *rocket = to.clone(),
+            }
+        }
+
+        /// The edit that takes the rocket back to how it was before this one.
+        fn inverse(&self) -> Edit {
+            match self {
+                Edit::Insert(at, components) => Edit::Remove(*at, components.clone()),
+                Edit::Remove(at, components) => Edit::Insert(*at, components.clone()),
+                &Edit::Move { from, to, len } => Edit::Move {
+                    from: to,
+                    to: from,
+                    len,
+                },
+                Edit::Rename { from, to } => Edit::Rename {
+                    from: to.clone(),
+                    to: from.clone(),
+                },
+                Edit::Replace { from, to } => Edit::Replace {
+                    from: to.clone(),
+                    to: from.clone(),
+                },
+            }
+        }
+
+        /// Where in the rocket the edit leaves its components, if it touches any in particular.
+        fn placed(&self) -> Option<Range<usize>> {
+            match self {
+                Edit::Insert(at, components) => Some(*at..*at + components.len()),
+                Edit::Remove(at, _) => Some(*at..*at),
+                &Edit::Move { to, len, .. } => Some(to..to + len),
+                Edit::Rename { .. } | Edit::Replace { .. } => None,
+            }
+        }
+    }
+
+    /// Returned by the builder when it leaves, with the index of the design it saved.
+    pub struct Saved(pub usize);
+
+    /// What can be done when leaving with unsaved changes, in the order they are offered.
+    const LEAVE_OPTIONS: [&str; 3] = ["Save", "Discard", "Keep editing"];
+
+    lazy_static! {
+        /// The names given to designs, shared by every box that names one.
+        static ref NAMES: Mutex<History> = Mutex::new(History::new());
+        /// The components last cut or copied, which can be pasted into any design.
+        static ref CLIPBOARD: Mutex<Vec<Component>> = Mutex::new(Vec::new());
+    }
+
+    /// An empty design, named so it does not clash with any saved one.
+    fn new_design() -> Rocket {
+        let rocket = Rocket::new();
+        let designs = GAME.rocket_designs.lock().unwrap();
+        Rocket {
+            name: super::rockets_view::free_name(&designs, &rocket.name),
+            ..rocket
+        }
+    }
+
+    /// A box for naming a design, which refuses a name another design already has. `editing` is
+    /// the saved design being named, if it is one.
+    pub fn name_box(editing: Option<usize>) -> TypeBox {
+        let others = GAME
+            .rocket_designs
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .filter(|&(idx, _)| Some(idx) != editing)
+            .map(|(_, rocket)| rocket.name.clone())
+            .collect();
+        TypeBox::new()
+            .with_width(20)
+            .validate(Validator::NonEmpty)
+            .validate(Validator::Unique(others))
+            .with_history(&NAMES)
+    }
+
+    impl FullView for View {
+        fn full_redraw(&self) {
+            self.name.before_render();
+            self.hits.clear();
+
+            ui_print!("{}", clear::All);
+            let screen = Rect::view();
+            let diagram = RocketDiagram::new(&self.rocket);
+            //The rocket is drawn beside everything else, where there is room
+            let main = if diagram.width() > 0 && screen.width >= 40 + diagram.width() + 2 {
+                let cols = screen.columns(&[Size::Fill, Size::Fixed(diagram.width() + 2)]);
+                diagram.draw(cols[1].indent(2));
+                cols[0]
+            } else {
+                screen
+            };
+            let parts = main.rows(&[
+                Size::Fixed(1),
+                Size::Fixed(2),
+                Size::Fixed(2),
+                Size::Fixed(1),
+                Size::Fill,
+            ]);
+
+            let save = Button::new("save").focused(matches!(self.sel, Sel::Save));
+            let header = parts[0].columns(&[
+                Size::Fixed(self.name.width()),
+                Size::Fixed(2),
+                Size::Fixed(save.width()),
+            ]);
+            save.draw(header[2]);
+            self.hits.add(header[0], (Sel::Name, 0));
+            self.hits.add(header[2], (Sel::Save, 0));
+
+            let components = GAME.known_components.lock().unwrap();
+            let max_width = Component::max_width(components.iter().chain(&self.rocket.components));
+
+            let slots = parts[2].indent(1).columns(&vec![
+                Size::Fixed(max_width + 1);
+                self.rocket.components.len()
+            ]);
+            let selection = self.selection().unwrap_or(0..0);
+            for (idx, (component, slot)) in self.rocket.components.iter().zip(slots).enumerate() {
+                Label::new(component.to_string()).draw(slot.line(0));
+                if selection.contains(&idx) {
+                    Label::new("^").draw(slot.line(1).indent(max_width / 2));
+                }
+                self.hits.add(slot, (Sel::RocketComponent(idx), 0));
+            }
+            if let Some(at) = self.insert_at {
+                //In the gap before the component it goes in front of
+                let x = parts[2].x + at as u16 * (max_width + 1);
+                Label::new("|").draw(Rect::new(x, parts[2].y, 1, 1));
+            }
+
+            Label::new("Components:").draw(parts[3]);
+            self.catalog.draw(parts[4], components.len(), |idx, area| {
+                let component = &components[idx];
+                let cols = area
+                    .line(0)
+                    .columns(&[Size::Fixed(max_width + 1), Size::Fill]);
+                Label::new(component.to_string()).draw(cols[0]);
+                Label::new(format!("{} ({})", component.name, component.class.kind()))
+                    .draw(cols[1]);
+                Label::new(format!("Mass: {}", component.mass))
+                    .draw(area.line(1).indent(max_width + 4));
+                self.hits.add(area, (Sel::NewComponent, idx));
+            });
+            drop(components);
+
+            self.name.draw(header[0]);
+            if self.name.error().is_some() {
+                self.name.draw_error(parts[1]);
+            }
+
+            self.name.after_render();
+        }
+
+        fn update(&mut self, input: Input) -> Option<Transition> {
+            if self.name.take_input(&input) {
+                self.full_redraw();
+            }
+
+            let in_catalog = matches!(self.sel, Sel::NewComponent);
+            let leaves_catalog =
+                input == Input::Up && self.catalog.selected == 0 && !self.catalog.is_searching();
+            if (in_catalog && !leaves_catalog) || input == Input::Search {
+                let len = GAME.known_components.lock().unwrap().len();
+                match self.catalog.take_input(input, len, View::label) {
+                    Taken::Ignored => {}
+                    Taken::Redraw => {
+                        self.set_sel(Sel::NewComponent);
+                        self.full_redraw();
+                        return None;
+                    }
+                    Taken::Transition(trans) => {
+                        self.set_sel(Sel::NewComponent);
+                        self.full_redraw();
+                        return Some(trans);
+                    }
+                }
+            }
+
+            match input {
+                Input::Back if self.dirty() => Some(self.ask_to_save()),
+                Input::Back => Some(self.leave()),
+                Input::Up => {
+                    match self.sel {
+                        Sel::RocketComponent(_) => self.set_sel(Sel::Save),
+                        Sel::NewComponent if !self.rocket.components.is_empty() => {
+                            self.set_sel(Sel::RocketComponent(0))
+                        }
+                        Sel::NewComponent => self.set_sel(Sel::Save),
+                        Sel::Save | Sel::Name => {}
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::Down => {
+                    match self.sel {
+                        Sel::RocketComponent(_) => self.set_sel(Sel::NewComponent),
+                        Sel::Save if !self.rocket.components.is_empty() => {
+                            self.set_sel(Sel::RocketComponent(0))
+                        }
+                        Sel::Save => self.set_sel(Sel::NewComponent),
+                        Sel::NewComponent | Sel::Name => {}
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::Right => {
+                    if let Sel::RocketComponent(idx) = self.sel {
+                        let next = (idx + 1) % self.rocket.components.len();
+                        self.set_sel(Sel::RocketComponent(next));
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::Left => match self.sel {
+                    Sel::RocketComponent(idx) => {
+                        let len = self.rocket.components.len();
+                        self.set_sel(Sel::RocketComponent((idx + len - 1) % len));
+                        self.full_redraw();
+                        None
+                    }
+                    Sel::NewComponent => None,
+                    Sel::Save => {
+                        self.set_sel(Sel::Name);
+                        self.name.activate(true);
+                        self.full_redraw();
+                        Some(Transition::InputMode(InputMode::Type))
+                    }
+                    Sel::Name => None,
+                },
+                Input::Select | Input::Type('\n') => match self.sel {
+                    Sel::RocketComponent(_) => None,
+                    Sel::NewComponent => {
+                        let component =
+                            GAME.known_components.lock().unwrap()[self.catalog.selected].clone();
+                        let at = self.insert_at.unwrap_or(self.rocket.components.len());
+                        self.edit(Edit::Insert(at, vec![component]));
+                        if let Some(at) = &mut self.insert_at {
+                            //The next goes after this one
+                            *at += 1;
+                        }
+                        self.full_redraw();
+                        None
+                    }
+                    Sel::Save => match self.save() {
+                        Ok(_) => None,
+                        Err(e) => Some(Transition::Dialog(Box::new(Alert::new(e)))),
+                    },
+                    Sel::Name => {
+                        let named = self.finish_naming();
+                        self.full_redraw();
+                        named.then(|| Transition::InputMode(InputMode::Control))
+                    }
+                },
+                Input::Del => {
+                    self.remove_selection();
+                    self.full_redraw();
+                    None
+                }
+                Input::Mark => {
+                    if let Sel::RocketComponent(idx) = self.sel {
+                        self.mark = match self.mark {
+                            Some(_) => None,
+                            None => Some(idx),
+                        };
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::Copy | Input::Cut => {
+                    if let Some(range) = self.selection() {
+                        *CLIPBOARD.lock().unwrap() = self.rocket.components[range].to_vec();
+                        if input == Input::Cut {
+                            self.remove_selection();
+                        } else {
+                            self.mark = None;
+                        }
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::Paste => {
+                    let at = match self.sel {
+                        Sel::RocketComponent(idx) => idx + 1,
+                        _ => self.rocket.components.len(),
+                    };
+                    let pasted = CLIPBOARD.lock().unwrap().clone();
+                    self.insert(at, pasted);
+                    self.full_redraw();
+                    None
+                }
+                Input::Duplicate => {
+                    if let Some(range) = self.selection() {
+                        let copies = self.rocket.components[range.clone()].to_vec();
+                        self.insert(range.end, copies);
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::MoveLeft | Input::MoveRight => {
+                    if let Some(range) = self.selection() {
+                        let to = match input {
+                            Input::MoveLeft => range.start.saturating_sub(1),
+                            _ => range.start + 1,
+                        };
+                        self.move_selection(to);
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::Undo | Input::Redo => {
+                    if input == Input::Undo {
+                        self.undo();
+                    } else {
+                        self.redo();
+                    }
+                    self.full_redraw();
+                    None
+                }
+                Input::InsertBefore | Input::InsertAfter => {
+                    if let Some(range) = self.selection() {
+                        let at = match input {
+                            Input::InsertBefore => range.start,
+                            _ => range.end,
+                        };
+                        self.set_sel(Sel::NewComponent);
+                        self.insert_at = Some(at);
+                    }
+                    self.full_redraw();
+                    None
+                }
+                _ => None,
+            }
+        }
+
+        fn start(&mut self) -> Option<Transition> {
+            self.name.set_content(self.rocket.name.as_str());
+            self.full_redraw();
+            None
+        }
+
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
+        }
+
+        fn click(&mut self, target: usize) -> Option<Transition> {
+            let (clicked, idx) = self.hits.get(target)?;
+            let mut transitions = Vec::new();
+            if let Sel::Name = self.sel {
+                if let Sel::Name = clicked {
+                    return None;
+                }
+                transitions.extend(self.update(Input::Select));
+            }
+            let trans = match clicked {
+                //Pressing on the marked components keeps them marked, so they can be dragged
+                Sel::RocketComponent(idx) if self.selection().unwrap_or(0..0).contains(&idx) => {
+                    None
+                }
+                Sel::RocketComponent(_) => {
+                    self.set_sel(clicked);
+                    self.mark = None;
+                    self.full_redraw();
+                    None
+                }
+                Sel::NewComponent | Sel::Save => {
+                    self.catalog.selected = idx;
+                    self.set_sel(clicked);
+                    self.update(Input::Select)
+                }
+                Sel::Name => {
+                    self.set_sel(Sel::Save);
+                    self.update(Input::Left)
+                }
+            };
+            transitions.extend(trans);
+            Some(Transition::Multiple(transitions))
+        }
+
+        fn drag(&mut self, from: usize, to: usize) -> Option<Transition> {
+            if let ((Sel::RocketComponent(_), _), (Sel::RocketComponent(to), _)) =
+                (self.hits.get(from)?, self.hits.get(to)?)
+            {
+                self.move_selection(to);
+                self.full_redraw();
+            }
+            None
+        }
+
+        fn dialog_result(&mut self, result: DialogResult) -> Option<Transition> {
+            let held_back = std::mem::take(&mut self.held_back);
+            match result {
+                DialogResult::Picked(Some(0)) => {
+                    let new = matches!(self.edited, Edited::New);
+                    let idx = match self.save() {
+                        Ok(idx) => idx,
+                        Err(e) => return Some(Transition::Dialog(Box::new(Alert::new(e)))),
+                    };
+                    if held_back {
+                        Some(Transition::Proceed)
+                    } else if new {
+                        //Show the new design rather than going straight back to the list
+                        Some(Transition::Replace(Box::new(
+                            super::rocket_detail_view::View::new(idx),
+                        )))
+                    } else {
+                        Some(self.leave())
+                    }
+                }
+                DialogResult::Picked(Some(1)) => {
+                    self.discarded = true;
+                    Some(if held_back {
+                        Transition::Proceed
+                    } else {
+                        Transition::Pop
+                    })
+                }
+                _ => None,
+            }
+        }
+
+        fn can_leave(&mut self) -> Option<Transition> {
+            if self.dirty() && !self.discarded {
+                self.held_back = true;
+                Some(self.ask_to_save())
+            } else {
+                None
+            }
+        }
+
+        fn title(&self) -> String {
+            match (&self.edited, self.rocket.name.as_str()) {
+                (Edited::New, "") => "New rocket".to_string(),
+                (_, name) => name.to_string(),
+            }
+        }
+
+        fn actions(&self, mode: InputMode) -> Vec<Action> {
+            if self.catalog.is_searching() {
+                return self.catalog.actions();
+            }
+            if mode == InputMode::Type {
+                let mut actions = self.name.actions();
+                actions.push((Input::Type('\n'), "Finish naming"));
+                return actions;
+            }
+            let mut actions = match self.sel {
+                Sel::NewComponent => self.catalog.actions(),
+                _ => vec![
+                    (Input::Up, "Up a row"),
+                    (Input::Down, "Down a row"),
+                    (Input::Search, "Search the catalog"),
+                ],
+            };
+            match self.sel {
+                Sel::RocketComponent(_) => actions.extend_from_slice(&[
+                    (Input::Left, "Previous component"),
+                    (Input::Right, "Next component"),
+                    (Input::Del, "Remove the components"),
+                    (Input::Mark, "Mark from here, to act on several"),
+                    (Input::Cut, "Cut the components"),
+                    (Input::Copy, "Copy the components"),
+                    (Input::Paste, "Paste after the component"),
+                    (Input::Duplicate, "Duplicate the components"),
+                    (Input::MoveLeft, "Move the components back"),
+                    (Input::MoveRight, "Move the components forward"),
+                    (Input::InsertBefore, "Add components before"),
+                    (Input::InsertAfter, "Add components after"),
+                ]),
+                Sel::NewComponent => actions.extend_from_slice(&[
+                    (Input::Select, "Add the component"),
+                    (Input::Paste, "Paste at the end"),
+                ]),
+                Sel::Save => actions.extend_from_slice(&[
+                    (Input::Select, "Save the design"),
+                    (Input::Left, "Rename the design"),
+                    (Input::Paste, "Paste at the end"),
+                ]),
+                Sel::Name => {}
+            }
+            actions.push((Input::Undo, "Undo"));
+            actions.push((Input::Redo, "Redo"));
+            actions.push((Input::Back, "Go back"));
+            actions
+        }
+
+        fn min_size(&self) -> (u16, u16) {
+            (40, 12)
+        }
+
+        fn commands(&self) -> Vec<Command> {
+            vec![
+                Command::in_view("save", None, 0),
+                Command::in_view("undo", Some("times"), 1),
+                Command::in_view("redo", Some("times"), 2),
+                Command::in_view("revert to saved", None, 3),
+            ]
+        }
+
+        fn run_command(&mut self, id: usize, times: Option<u32>) -> Ran {
+            let naming = matches!(self.sel, Sel::Name);
+            if id != 0 {
+                if naming {
+                    //Whatever is typed is dropped, as it was never part of the design
+                    self.name.activate(false);
+                    self.set_sel(Sel::Save);
+                }
+                for _ in 0..times.unwrap_or(1) {
+                    match id {
+                        1 => self.undo(),
+                        2 => self.redo(),
+                        _ => self.revert(),
+                    }
+                }
+                self.full_redraw();
+                return Ok(naming.then(|| Transition::InputMode(InputMode::Control)));
+            }
+            if naming && !self.finish_naming() {
+                return Err(format!(
+                    "Can not save: {}",
+                    self.name.error().unwrap_or_default()
+                ));
+            }
+            self.save()?;
+            Ok(naming.then(|| Transition::InputMode(InputMode::Control)))
+        }
+    }
+
+    impl View {
+        pub fn new_rocket() -> View {
+            View {
+                rocket: new_design(),
+                edited: Edited::New,
+                sel: Sel::NewComponent,
+                name: name_box(None),
+                catalog: ScrollList::new(2),
+                hits: HitMap::new(),
+                undo: Vec::new(),
+                redo: Vec::new(),
+                saved_at: Some(0),
+                mark: None,
+                insert_at: None,
+                held_back: false,
+                discarded: false,
+            }
+        }
+
+        pub fn edit_rocket(idx: usize) -> View {
+            let rocket = GAME.rocket_designs.lock().unwrap()[idx].clone();
+            let mut view = View {
+                sel: Sel::NewComponent,
+                edited: Edited::Edit(idx),
+                name: name_box(Some(idx)),
+                catalog: ScrollList::new(2),
+                hits: HitMap::new(),
+                undo: Vec::new(),
+                redo: Vec::new(),
+                saved_at: Some(0),
+                mark: None,
+                insert_at: None,
+                held_back: false,
+                discarded: false,
+                rocket,
+            };
+            if !view.rocket.components.is_empty() {
+                view.set_sel(Sel::RocketComponent(0));
+            }
+            view
+        }
+
+        /// Saves the design, returning where it is in the list of designs, unless another design
+        /// already has its name.
+        fn save(&mut self) -> Result<usize, String> {
+            let taken = GAME
+                .rocket_designs
+                .lock()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .filter(|&(idx, _)| !matches!(self.edited, Edited::Edit(edited) if edited == idx))
+                .any(|(_, rocket)| rocket.name.to_lowercase() == self.rocket.name.to_lowercase());
+            if taken {
+                return Err(format!(
+                    "Can not save: {} is already a design",
+                    self.rocket.name
+                ));
+            }
+            let idx = match self.edited {
+                Edited::Edit(idx) => {
+                    GAME.rocket_designs.lock().unwrap()[idx] = self.rocket.clone();
+                    idx
+                }
+                Edited::New => {
+                    let mut rocket_designs = GAME.rocket_designs.lock().unwrap();
+                    rocket_designs.push(self.rocket.clone());
+                    rocket_designs.len() - 1
+                }
+            };
+            self.edited = Edited::Edit(idx);
+            self.saved_at = Some(self.undo.len());
+            Ok(idx)
+        }
+
+        /// Whether the rocket has changed since it was last saved.
+        fn dirty(&self) -> bool {
+            self.saved_at != Some(self.undo.len())
+        }
+
+        /// Makes a change to the rocket that can be undone.
+        fn edit(&mut self, edit: Edit) {
+            edit.apply(&mut self.rocket);
+            if matches!(self.saved_at, Some(at) if at > self.undo.len()) {
+                //The saved design was only reachable by redoing, which this edit ends
+                self.saved_at = None;
+            }
+            self.redo.clear();
+            self.undo.push(edit);
+        }
+
+        fn undo(&mut self) {
+            if let Some(edit) = self.undo.pop() {
+                let inverse = edit.inverse();
+                inverse.apply(&mut self.rocket);
+                self.show_edit(&inverse);
+                self.redo.push(edit);
+            }
+        }
+
+        fn redo(&mut self) {
+            if let Some(edit) = self.redo.pop() {
+                edit.apply(&mut self.rocket);
+                self.show_edit(&edit);
+                self.undo.push(edit);
+            }
+        }
+
+        /// Puts the rocket back as it was last saved, which can itself be undone.
+        fn revert(&mut self) {
+            if !self.dirty() {
+                return;
+            }
+            let saved = match self.edited {
+                Edited::Edit(idx) => GAME.rocket_designs.lock().unwrap()[idx].clone(),
+                Edited::New => new_design(),
+            };
+            let from = self.rocket.clone();
+            self.edit(Edit::Replace { from, to: saved });
+            self.saved_at = Some(self.undo.len());
+            self.name.set_content(self.rocket.name.as_str());
+            self.mark = None;
+            self.keep_sel_in_rocket();
+        }
+
+        /// Selects what an undone or redone edit changed, so the player can see it.
+        fn show_edit(&mut self, edit: &Edit) {
+            self.name.set_content(self.rocket.name.as_str());
+            match edit.placed() {
+                Some(range) if !range.is_empty() => self.select(range),
+                Some(range) => {
+                    self.mark = None;
+                    if let Sel::RocketComponent(_) = self.sel {
+                        self.set_sel(Sel::RocketComponent(range.start));
+                    }
+                }
+                None => {}
+            }
+            self.keep_sel_in_rocket();
+        }
+
+        /// Moves the selection and insert point back onto the rocket after it got shorter.
+        fn keep_sel_in_rocket(&mut self) {
+            let len = self.rocket.components.len();
+            if let Sel::RocketComponent(idx) = self.sel {
+                if len == 0 {
+                    self.set_sel(Sel::NewComponent);
+                } else if idx >= len || self.mark.unwrap_or(0) >= len {
+                    self.mark = None;
+                    self.set_sel(Sel::RocketComponent(idx.min(len - 1)));
+                }
+            }
+            if let Some(at) = &mut self.insert_at {
+                *at = (*at).min(len);
+            }
+        }
+
+        /// Asks whether to save the unsaved changes before leaving. A name being typed is kept if it
+        /// can be, or else dropped.
+        fn ask_to_save(&mut self) -> Transition {
+            if matches!(self.sel, Sel::Name) && !self.finish_naming() {
+                self.name.set_content(self.rocket.name.as_str());
+                self.name.activate(false);
+                self.set_sel(Sel::Save);
+            }
+            let options = LEAVE_OPTIONS.iter().map(|o| o.to_string()).collect();
+            Transition::Dialog(Box::new(Choice::new("Unsaved changes", options)))
+        }
+
+        /// Goes back, telling the view below which design was saved, if one was.
+        fn leave(&self) -> Transition {
+            match self.edited {
+                Edited::Edit(idx) => Transition::Return(Box::new(Saved(idx))),
+                Edited::New => Transition::Pop,
+            }
+        }
+
+        /// Stops typing in the name box and takes its content as the rocket's name, unless the
+        /// name is refused, in which case the box shows why and typing goes on.
+        fn finish_naming(&mut self) -> bool {
+            let name = match self.name.submit() {
+                Some(name) => name,
+                None => return false,
+            };
+            self.name.activate(false);
+            if self.rocket.name != name {
+                let from = self.rocket.name.clone();
+                self.edit(Edit::Rename { from, to: name });
+            }
+            self.set_sel(Sel::Save);
+            true
+        }
+
+        /// Moves the selection, showing the catalog's marker only while it is selected. Leaving
+        /// the rocket unmarks its components, and leaving the catalog forgets where to insert.
+        fn set_sel(&mut self, sel: Sel) {
+            if !matches!(sel, Sel::RocketComponent(_)) {
+                self.mark = None;
+            }
+            if !matches!(sel, Sel::NewComponent) {
+                self.insert_at = None;
+            }
+            self.sel = sel;
+            self.catalog.focused = matches!(sel, Sel::NewComponent);
+        }
+
+        /// The selected component and any others marked with it.
+        fn selection(&self) -> Option<Range<usize>> {
+            match self.sel {
+                Sel::RocketComponent(idx) => {
+                    let mark = self.mark.unwrap_or(idx);
+                    Some(idx.min(mark)..idx.max(mark) + 1)
+                }
+                _ => None,
+            }
+        }
+
+        /// Selects the components in `range`, with the selected end on the same side as before.
+        fn select(&mut self, range: Range<usize>) {
+            let last = range.end - 1;
+            let backwards = match (self.sel, self.mark) {
+                (Sel::RocketComponent(idx), Some(mark)) => idx < mark,
+                _ => false,
+            };
+            let (idx, mark) = if backwards {
+                (range.start, last)
+            } else {
+                (last, range.start)
+            };
+            self.set_sel(Sel::RocketComponent(idx));
+            self.mark = if range.len() > 1 { Some(mark) } else { None };
+        }
+
+        fn remove_selection(&mut self) {
+            let range = match self.selection() {
+                Some(range) => range,
+                None => return,
+            };
+            let start = range.start;
+            let removed = self.rocket.components[range].to_vec();
+            self.edit(Edit::Remove(start, removed));
+            self.mark = None;
+            let len = self.rocket.components.len();
+            if len == 0 {
+                self.set_sel(Sel::NewComponent);
+            } else {
+                self.set_sel(Sel::RocketComponent(start.min(len - 1)));
+            }
+        }
+
+        /// Puts `components` in at `at`, selecting them.
+        fn insert(&mut self, at: usize, components: Vec<Component>) {
+            if components.is_empty() {
+                return;
+            }
+            let len = components.len();
+            self.edit(Edit::Insert(at, components));
+            self.mark = None;
+            self.select(at..at + len);
+        }
+
+        /// Moves the selected components along so the first is at `to`, or as near as fits.
+        fn move_selection(&mut self, to: usize) {
+            let range = match self.selection() {
+                Some(range) => range,
+                None => return,
+            };
+            let len = range.len();
+            let to = to.min(self.rocket.components.len() - len);
+            if to != range.start {
+                self.edit(Edit::Move {
+                    from: range.start,
+                    to,
+                    len,
+                });
+            }
+            self.select(to..to + len);
+        }
+
+        fn label(idx: usize) -> String {
+            GAME.known_components.lock().unwrap()[idx].name.clone()
+        }
+    }
+}
+
+mod settings_view {
+    use super::view_prelude::*;
+    use crate::debug_log::DEBUG;
+    use crate::settings::Choice;
+    use crate::settings::Settings;
+    use termion::clear;
 
-            for (idx, rocket) in rockets.iter().enumerate() {
-                print!("{}", cursor::Goto(2, (2 + 2 * idx) as u16));
-                ui_print!("{}: {}", rocket.name, rocket);
-            }
+    pub struct View {
+        sel: usize,
+        hits: HitMap<usize>,
+    }
 
-            drop(rockets);
+    #[derive(Clone, Copy)]
+    enum Field {
+        Units,
+        Difficulty,
+        TickSpeed,
+        DebugOverlay,
+        GlyphMode,
+        Numerals,
+        KeyBindings,
+    }
 
-            const EDIT_BUTTON_X: u16 = 40;
+    const FIELDS: [Field; 7] = [
+        Field::Units,
+        Field::Difficulty,
+        Field::TickSpeed,
+        Field::DebugOverlay,
+        Field::GlyphMode,
+        Field::Numerals,
+        Field::KeyBindings,
+    ];
 
-            match self.sel {
-                Sel::New => print!("{}{{{}}}", cursor::Goto(10, 1), cursor::Right(1)),
-                Sel::Rocket(idx) => print!(
-                    "{}>{}(edit)",
-                    cursor::Goto(1, (2 + 2 * idx) as u16),
-                    cursor::Right(EDIT_BUTTON_X - 1)
-                ),
-                Sel::RocketEdit(idx) => print!(
-                    "{}[edit]",
-                    cursor::Goto(EDIT_BUTTON_X, (2 + 2 * idx) as u16)
-                ),
-            }
+    impl FullView for View {
+        fn full_redraw(&self) {
+            self.hits.clear();
+            ui_print!("{}", clear::All);
 
-            stdout().flush().unwrap();
+            let settings = SETTINGS.lock().unwrap().clone();
+            let list = List {
+                selected: Some(self.sel),
+                item_height: 1,
+            };
+            list.draw(Rect::view(), FIELDS.len(), |idx, area| {
+                let field = FIELDS[idx];
+                let cols = area.columns(&[Size::Fixed(17), Size::Fill]);
+                Label::new(field.label()).draw(cols[0]);
+                if let Some(value) = field.value(&settings) {
+                    Label::new(format!("< {} >", value)).draw(cols[1]);
+                }
+                self.hits.add(area, idx);
+            });
         }
 
         fn update(&mut self, input: Input) -> Option<Transition> {
             match input {
-                Input::Back => Some(Transition::Pop),
-                Input::Up => {
-                    let rocket_cnt = (GAME.rocket_designs.lock().unwrap().len()) as u8;
-                    if rocket_cnt == 0 {
-                        self.sel = Sel::New;
-                        return None;
-                    }
-                    let max_idx = rocket_cnt - 1;
-                    match self.sel {
-                        Sel::New => self.sel = Sel::Rocket(max_idx),
-                        Sel::Rocket(idx) | Sel::RocketEdit(idx) => {
-                            self.sel = if idx == 0 {
-                                Sel::New
-                            } else {
-                                Sel::Rocket(idx - 1)
-                            }
+                Input::Back => {
+                    let saved = SETTINGS.lock().unwrap().save();
+                    match saved {
+                        Ok(()) => Some(Transition::Pop),
+                        Err(e) => {
+                            DEBUG.log(&format!("Could not save settings: {}", e));
+                            Some(Transition::Dialog(Box::new(Alert::new(
+                                "Could not save settings",
+                            ))))
                         }
                     }
+                }
+                Input::Up => {
+                    self.sel = (self.sel + FIELDS.len() - 1) % FIELDS.len();
                     self.full_redraw();
                     None
                 }
                 Input::Down => {
-                    let rocket_cnt = (GAME.rocket_designs.lock().unwrap().len()) as u8;
-                    if rocket_cnt == 0 {
-                        self.sel = Sel::New;
-                        return None;
-                    }
-                    let max_idx = rocket_cnt - 1;
-                    match self.sel {
-                        Sel::New => self.sel = Sel::Rocket(0),
-                        Sel::Rocket(idx) | Sel::RocketEdit(idx) => {
-                            self.sel = if idx == max_idx {
-                                Sel::New
-                            } else {
-                                Sel::Rocket(idx + 1)
-                            }
-                        }
-                    }
+                    self.sel = (self.sel + 1) % FIELDS.len();
                     self.full_redraw();
                     None
                 }
-                Input::Left | Input::Right => {
-                    match self.sel {
-                        Sel::New => {}
-                        Sel::Rocket(idx) => {
-                            self.sel = Sel::RocketEdit(idx);
-                            self.full_redraw();
-                        }
-                        Sel::RocketEdit(idx) => {
-                            self.sel = Sel::Rocket(idx);
-                            self.full_redraw();
-                        }
-                    };
+                Input::Left => {
+                    FIELDS[self.sel].cycle(false);
+                    self.full_redraw();
+                    None
+                }
+                Input::Select if FIELDS[self.sel].opens_view() => {
+                    Some(Transition::Push(Box::new(super::keymap_view::View::new())))
+                }
+                Input::Right | Input::Select => {
+                    FIELDS[self.sel].cycle(true);
+                    self.full_redraw();
                     None
                 }
-                Input::Select => match self.sel {
-                    Sel::New => Some(Transition::Push(Box::new(
-                        super::rocket_builder_view::View::new_rocket(),
-                    ))),
-                    Sel::RocketEdit(idx) => Some(Transition::Push(Box::new(
-                        super::rocket_builder_view::View::edit_rocket(idx),
-                    ))),
-                    Sel::Rocket(_) => None,
-                },
                 _ => None,
             }
         }
 
-        fn start(&mut self) -> Option<Transition> {
-            self.check_idx();
-            self.full_redraw();
-            None
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
         }
 
-        fn restart(&mut self, _: Box<dyn FullView>) -> Option<Transition> {
-            self.check_idx();
-            self.full_redraw();
-            None
+        fn title(&self) -> String {
+            "Settings".to_string()
+        }
+
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            vec![
+                (Input::Up, "Previous setting"),
+                (Input::Down, "Next setting"),
+                (Input::Left, "Previous choice"),
+                (Input::Right, "Next choice"),
+                (Input::Select, "Next choice, or open"),
+                (Input::Back, "Save and go back"),
+            ]
+        }
+
+        fn click(&mut self, target: usize) -> Option<Transition> {
+            self.sel = self.hits.get(target)?;
+            self.update(Input::Select)
+        }
+
+        fn dialog_result(&mut self, _: DialogResult) -> Option<Transition> {
+            //The only dialog is the alert that settings could not be saved
+            Some(Transition::Pop)
+        }
+
+        fn min_size(&self) -> (u16, u16) {
+            (32, FIELDS.len() as u16)
         }
     }
 
-    impl View {
-        pub fn new() -> View {
-            View {
-                sel: Sel::Rocket(0),
+    impl Field {
+        fn label(self) -> &'static str {
+            match self {
+                Field::Units => "Units",
+                Field::Difficulty => "Difficulty",
+                Field::TickSpeed => "Auto-tick speed",
+                Field::DebugOverlay => "Debug overlay",
+                Field::GlyphMode => "Glyphs",
+                Field::Numerals => "Numerals",
+                Field::KeyBindings => "Key bindings...",
             }
         }
 
-        fn check_idx(&mut self) {
-            let rocket_cnt = GAME.rocket_designs.lock().unwrap().len() as u8;
-            if rocket_cnt == 0 {
-                self.sel = Sel::New;
-                return;
+        fn value(self, settings: &Settings) -> Option<&'static str> {
+            match self {
+                Field::Units => Some(settings.units.name()),
+                Field::Difficulty => Some(settings.difficulty.name()),
+                Field::TickSpeed => Some(settings.tick_speed.name()),
+                Field::DebugOverlay => Some(settings.debug_overlay.name()),
+                Field::GlyphMode => Some(settings.glyph_mode.name()),
+                Field::Numerals => Some(settings.numerals.name()),
+                Field::KeyBindings => None,
             }
-            let max_idx = rocket_cnt - 1;
-            match self.sel {
-                Sel::Rocket(idx) => {
-                    if idx > max_idx {
-                        self.sel = Sel::Rocket(idx - 1)
-                    }
-                }
-                Sel::RocketEdit(idx) => {
-                    if idx > max_idx {
-                        self.sel = Sel::RocketEdit(idx - 1)
-                    }
+        }
+
+        fn opens_view(self) -> bool {
+            matches!(self, Field::KeyBindings)
+        }
+
+        fn cycle(self, forward: bool) {
+            fn step<T: Choice>(field: &mut T, forward: bool) {
+                *field = if forward { field.next() } else { field.prev() };
+            }
+
+            let mut settings = SETTINGS.lock().unwrap();
+            match self {
+                Field::Units => step(&mut settings.units, forward),
+                Field::Difficulty => {
+                    step(&mut settings.difficulty, forward);
+                    let target = settings.difficulty.target_customers();
+                    drop(settings);
+                    GAME.customers.set_target_customers(target);
                 }
-                Sel::New => {}
+                Field::TickSpeed => step(&mut settings.tick_speed, forward),
+                Field::DebugOverlay => step(&mut settings.debug_overlay, forward),
+                Field::GlyphMode => step(&mut settings.glyph_mode, forward),
+                Field::Numerals => step(&mut settings.numerals, forward),
+                Field::KeyBindings => {}
+            }
+        }
+    }
+
+    impl View {
+        pub fn new() -> View {
+            View {
+                sel: 0,
+                hits: HitMap::new(),
             }
         }
     }
 }
 
-mod rocket_builder_view {
+mod keymap_view {
     use super::view_prelude::*;
-    use crate::rocket::Component;
-    use crate::rocket::Rocket;
-    use crate::ui_print;
-    use std::io::stdout;
-    use std::io::Write;
-    use termion::{clear, cursor};
+    use super::widget::selection_marker;
+    use crate::debug_log::DEBUG;
+    use crate::keymap::{self, Keymap, Preset, BINDABLE, KEYMAP, MODES};
+    use crate::settings::Choice;
+    use termion::clear;
+    use termion::event::Key;
 
     pub struct View {
-        edited: Edited,
-        rocket: Rocket,
-        sel: Sel,
-        name: TypeBox,
-    }
-
-    enum Sel {
-        RocketComponent(u8),
-        NewComponent(u8),
-        Save,
-        Name,
-    }
-
-    enum Edited {
-        New,
-        Edit(u8),
+        row: usize,
+        col: usize,
+        preset: Preset,
+        capturing: bool,
+        /// A key that conflicted with an existing binding, which is bound anyway if it is
+        /// pressed again.
+        pending: Option<Key>,
+        message: String,
+        hits: HitMap<(usize, usize)>,
+        /// Scrolls the bindings, whose row is one less than `row`. It draws no marker, as each
+        /// mode's column has its own.
+        list: ScrollList,
     }
 
     impl FullView for View {
         fn full_redraw(&self) {
-            self.name.before_render();
-
-            print!("{}{}", clear::All, cursor::Goto(1, 1));
-
-            print!("{}", self.rocket.name);
-
-            const SAVE_BUTTON_X: u16 = 30;
+            self.hits.clear();
+            ui_print!("{}", clear::All);
+            let parts = Rect::view().rows(&[
+                Size::Fixed(2),
+                Size::Fixed(1),
+                Size::Fill,
+                Size::Fixed(1),
+                Size::Fixed(1),
+            ]);
 
-            print!("{} save", cursor::Goto(SAVE_BUTTON_X, 1));
+            let marker = |selected: bool| if selected { selection_marker() } else { " " };
+            let preset = parts[0].line(0);
+            Label::new(format!(
+                "{} Preset: < {} >  (select to load)",
+                marker(self.row == 0),
+                self.preset.name()
+            ))
+            .draw(preset);
+            self.hits.add(preset, (0, 0));
 
-            print!("{}", cursor::Goto(2, 4));
-
-            for component in &self.rocket.components {
-                let symbol = format!("{}", component);
-                print!(
-                    "{}{}",
-                    symbol,
-                    cursor::Right(Component::MAX_WIDTH + 1 - symbol.len() as u16)
-                ); //this one really should be print, not ui_print
-            }
-
-            print!("{}Components:", cursor::Goto(1, 6));
-            let components = GAME.known_components.lock().unwrap();
-
-            for (idx, component) in components.iter().enumerate() {
-                ui_print!(
-                    "{}{}{}{} ({}){}Mass: {} kg",
-                    cursor::Goto(3, (7 + idx * 2) as u16),
-                    component,
-                    cursor::Goto(3 + Component::MAX_WIDTH, (7 + idx * 2) as u16),
-                    component.name,
-                    component.class.symbol(),
-                    cursor::Goto(6 + Component::MAX_WIDTH, (8 + idx * 2) as u16),
-                    component.mass.in_kg(),
-                );
+            let columns = [Size::Fixed(13), Size::Fill, Size::Fill];
+            let header = parts[1].indent(2).columns(&columns);
+            for (&mode, &area) in MODES.iter().zip(&header[1..]) {
+                Label::new(format!("  {} mode", keymap::mode_name(mode))).draw(area);
             }
 
-            match self.sel {
-                Sel::RocketComponent(idx) => {
-                    print!(
-                        "{}^",
-                        cursor::Goto(
-                            1 + (Component::MAX_WIDTH + 1) * (idx as u16) + Component::MAX_WIDTH / 2,
-                            5
-                        )
-                    );
-                }
-                Sel::NewComponent(idx) => {
-                    print!("{}+>", cursor::Goto(1, (7 + idx * 2) as u16));
+            let keymap = KEYMAP.lock().unwrap();
+            self.list.draw(parts[2], BINDABLE.len(), |idx, area| {
+                let input = BINDABLE[idx];
+                let cols = area.columns(&columns);
+                Label::new(keymap::input_name(input)).draw(cols[0]);
+                for (col, (&mode, &area)) in MODES.iter().zip(&cols[1..]).enumerate() {
+                    let keys: Vec<String> = keymap
+                        .keys_for(mode, input)
+                        .into_iter()
+                        .map(keymap::key_name)
+                        .collect();
+                    let selected = self.row == idx + 1 && self.col == col;
+                    Label::new(format!("{} {}", marker(selected), keys.join(", "))).draw(area);
+                    self.hits.add(area, (idx + 1, col));
                 }
-                Sel::Save => {
-                    print!("{}[{}]", cursor::Goto(SAVE_BUTTON_X, 1), cursor::Right(4));
-                }
-                Sel::Name => {}
-            }
-
-            self.name.draw();
-
-            self.name.after_render();
+            });
+            drop(keymap);
 
-            stdout().flush().unwrap();
+            Label::new(self.message.as_str()).draw(parts[4]);
         }
 
         fn update(&mut self, input: Input) -> Option<Transition> {
-            if self.name.take_input(&input) {
-                self.full_redraw();
+            if self.capturing {
+                return self.capture(input);
             }
 
             match input {
-                Input::Back => Some(Transition::Multiple(vec![
-                    Transition::InputMode(InputMode::Control),
-                    Transition::Pop,
-                ])),
-                Input::Up => {
-                    match self.sel {
-                        Sel::RocketComponent(_) => {
-                            self.sel = Sel::Save;
-                        }
-                        Sel::NewComponent(idx) => {
-                            if idx == 0 && !self.rocket.components.is_empty() {
-                                self.sel = Sel::RocketComponent(0);
-                            } else if idx == 0 {
-                                self.sel = Sel::Save;
-                            } else {
-                                self.sel = Sel::NewComponent(idx - 1);
-                            }
-                        }
-                        Sel::Save | Sel::Name => {}
+                Input::Back => {
+                    if let Err(e) = KEYMAP.lock().unwrap().save() {
+                        DEBUG.log(&format!("Could not save keymap: {}", e));
                     }
+                    Some(Transition::Pop)
+                }
+                Input::Up => {
+                    self.set_row((self.row + BINDABLE.len()) % (BINDABLE.len() + 1));
                     self.full_redraw();
                     None
                 }
                 Input::Down => {
-                    match self.sel {
-                        Sel::RocketComponent(_) => {
-                            self.sel = Sel::NewComponent(0);
-                        }
-                        Sel::NewComponent(idx) => {
-                            if idx as usize != GAME.known_components.lock().unwrap().len() - 1 {
-                                self.sel = Sel::NewComponent(idx + 1);
-                            }
-                        }
-                        Sel::Save => {
-                            self.sel = if !self.rocket.components.is_empty() {
-                                Sel::RocketComponent(0)
-                            } else {
-                                Sel::NewComponent(0)
-                            };
-                        }
-                        Sel::Name => {}
-                    }
+                    self.set_row((self.row + 1) % (BINDABLE.len() + 1));
                     self.full_redraw();
                     None
                 }
-                Input::Right => {
-                    match self.sel {
-                        Sel::RocketComponent(idx) => {
-                            if idx == self.rocket.components.len() as u8 - 1 {
-                                self.sel = Sel::RocketComponent(0);
-                            } else {
-                                self.sel = Sel::RocketComponent(idx + 1);
-                            }
-                        }
-                        Sel::NewComponent(_) | Sel::Save | Sel::Name => {}
+                Input::Left | Input::Right => {
+                    if self.row == 0 {
+                        self.preset = if input == Input::Left {
+                            self.preset.prev()
+                        } else {
+                            self.preset.next()
+                        };
+                    } else {
+                        self.col = (self.col + 1) % MODES.len();
                     }
                     self.full_redraw();
                     None
                 }
-                Input::Left => match self.sel {
-                    Sel::RocketComponent(idx) => {
-                        if idx == 0 {
-                            self.sel = Sel::RocketComponent(self.rocket.components.len() as u8 - 1);
-                        } else {
-                            self.sel = Sel::RocketComponent(idx - 1)
-                        }
-                        self.full_redraw();
-                        None
-                    }
-                    Sel::NewComponent(_) => None,
-                    Sel::Save => {
-                        self.sel = Sel::Name;
-                        self.name.activate(true);
-                        self.full_redraw();
-                        Some(Transition::InputMode(InputMode::Type))
-                    }
-                    Sel::Name => None,
-                },
-                Input::Select | Input::Type('\n') => match self.sel {
-                    Sel::RocketComponent(_) => None,
-                    Sel::NewComponent(idx) => {
-                        self.rocket
-                            .components
-                            .push(GAME.known_components.lock().unwrap()[idx as usize].clone());
+                Input::Select => {
+                    if self.row == 0 {
+                        *KEYMAP.lock().unwrap() = Keymap::preset(self.preset);
+                        self.message = format!("Loaded the {} preset", self.preset.name());
                         self.full_redraw();
                         None
-                    }
-                    Sel::Save => match self.edited {
-                        Edited::Edit(idx) => {
-                            GAME.rocket_designs.lock().unwrap()[idx as usize] = self.rocket.clone();
-                            None
-                        }
-                        Edited::New => {
-                            let mut rocket_designs = GAME.rocket_designs.lock().unwrap();
-                            rocket_designs.push(self.rocket.clone());
-                            self.edited = Edited::Edit(rocket_designs.len() as u8 - 1);
-                            None
-                        }
-                    },
-                    Sel::Name => {
-                        self.name.activate(false);
-                        self.rocket.name = self.name.content.clone();
-                        self.sel = Sel::Save;
+                    } else {
+                        self.capturing = true;
+                        self.message = format!(
+                            "Press a key for {} in {} mode (Esc cancels)",
+                            keymap::input_name(self.input()),
+                            keymap::mode_name(self.mode())
+                        );
                         self.full_redraw();
-                        Some(Transition::InputMode(InputMode::Control))
+                        Some(Transition::InputMode(InputMode::Capture))
                     }
-                },
-                Input::Del => match self.sel {
-                    Sel::RocketComponent(idx) => {
-                        self.rocket.components.remove(idx as usize);
-                        if idx as usize >= self.rocket.components.len() {
-                            if self.rocket.components.is_empty() {
-                                self.sel = Sel::NewComponent(0);
-                            } else {
-                                self.sel =
-                                    Sel::RocketComponent(self.rocket.components.len() as u8 - 1);
-                            }
+                }
+                Input::Del if self.row != 0 => {
+                    if self.mode() == InputMode::Control
+                        && (self.input() == Input::Back || self.input() == Input::Select)
+                    {
+                        self.message = format!(
+                            "{} must stay bound in control mode",
+                            keymap::input_name(self.input())
+                        );
+                    } else {
+                        let mut keymap = KEYMAP.lock().unwrap();
+                        for key in keymap.keys_for(self.mode(), self.input()) {
+                            keymap.unbind(self.mode(), key);
                         }
-                        self.full_redraw();
-                        None
+                        self.message = String::new();
                     }
-                    _ => None,
-                },
+                    self.full_redraw();
+                    None
+                }
                 _ => None,
             }
         }
 
-        fn start(&mut self) -> Option<Transition> {
-            self.name.content = self.rocket.name.clone();
-            self.full_redraw();
-            None
+        fn hit_regions(&self) -> Vec<HitRegion> {
+            self.hits.regions()
         }
-    }
 
-    impl View {
-        pub fn new_rocket() -> View {
-            View {
-                rocket: Rocket::new(),
-                edited: Edited::New,
-                sel: Sel::NewComponent(0),
-                name: TypeBox::new().at(1, 1).with_len(20),
-            }
+        fn title(&self) -> String {
+            "Key Bindings".to_string()
         }
 
-        pub fn edit_rocket(idx: u8) -> View {
-            View {
-                rocket: GAME.rocket_designs.lock().unwrap()[idx as usize].clone(),
-                edited: Edited::Edit(idx),
-                sel: Sel::RocketComponent(0),
-                name: TypeBox::new().at(1, 1).with_len(20),
-            }
+        fn actions(&self, _: InputMode) -> Vec<Action> {
+            vec![
+                (Input::Up, "Previous row"),
+                (Input::Down, "Next row"),
+                (Input::Left, "Previous preset or mode"),
+                (Input::Right, "Next preset or mode"),
+                (Input::Select, "Load the preset, or bind a key"),
+                (Input::Del, "Unbind the keys"),
+                (Input::Back, "Save and go back"),
+            ]
         }
-    }
-}
-
-mod exit_confirmation_view {
-    use super::view_prelude::*;
-    use std::io::stdout;
-    use std::io::Write;
-    use termion::clear;
-    use termion::cursor;
 
-    pub struct View {
-        inner_view: Option<Box<dyn FullView>>,
-        sel: Sel,
-    }
+        fn click(&mut self, target: usize) -> Option<Transition> {
+            let (row, col) = self.hits.get(target)?;
+            self.set_row(row);
+            self.col = col;
+            self.update(Input::Select)
+        }
 
-    #[derive(PartialEq, Eq)]
-    enum Sel {
-        Yes,
-        No,
+        fn min_size(&self) -> (u16, u16) {
+            (50, 10)
+        }
     }
 
-    impl FullView for View {
-        fn full_redraw(&self) {
-            print!("{}", clear::All);
-            if let Some(inner) = &self.inner_view {
-                inner.full_redraw();
-            }
-
-            const X_START: u16 = 3;
-            const Y_START: u16 = 2;
-            const WIDTH: u16 = 34;
-            const HEIGHT: u16 = 4;
-
-            print!(
-                "{}+{}+",
-                cursor::Goto(X_START, Y_START),
-                "-".repeat(WIDTH as usize - 2)
-            );
-            for y in (Y_START + 1)..(Y_START + HEIGHT - 1) {
-                print!(
-                    "{}|{}|",
-                    cursor::Goto(X_START, y),
-                    " ".repeat(WIDTH as usize - 2)
-                );
+    impl View {
+        pub fn new() -> View {
+            let mut list = ScrollList::new(1);
+            list.focused = false;
+            View {
+                row: 0,
+                col: 0,
+                preset: Preset::Default,
+                capturing: false,
+                pending: None,
+                message: String::new(),
+                hits: HitMap::new(),
+                list,
             }
+        }
 
-            print!(
-                "{}+{}+",
-                cursor::Goto(X_START, Y_START + HEIGHT - 1),
-                "-".repeat(WIDTH as usize - 2)
-            );
+        fn set_row(&mut self, row: usize) {
+            self.row = row;
+            self.list.selected = row.saturating_sub(1);
+        }
 
-            print!(
-                "{}Are you sure you want to exit?",
-                cursor::Goto(X_START + 2, Y_START + 1)
-            );
-            print!(
-                "{} YES {} NO ",
-                cursor::Goto(X_START + 5, Y_START + 2),
-                cursor::Right(5)
-            );
+        fn mode(&self) -> InputMode {
+            MODES[self.col]
+        }
 
-            let sel_x = match self.sel {
-                Sel::Yes => X_START + 5,
-                Sel::No => X_START + 15,
-            };
+        fn input(&self) -> Input {
+            BINDABLE[self.row - 1]
+        }
 
-            let sel_width = match self.sel {
-                Sel::Yes => 3,
-                Sel::No => 2,
+        fn capture(&mut self, input: Input) -> Option<Transition> {
+            let key = match input {
+                Input::Key(key) => key,
+                _ => return None,
             };
+            if key == Key::Esc {
+                self.capturing = false;
+                self.pending = None;
+                self.message = String::new();
+                self.full_redraw();
+                return Some(Transition::InputMode(InputMode::Control));
+            }
 
-            print!(
-                "{}[{}]",
-                cursor::Goto(sel_x, Y_START + 2),
-                cursor::Right(sel_width)
-            );
-
-            stdout().flush().unwrap();
-        }
-
-        fn update(&mut self, input: Input) -> Option<Transition> {
-            match input {
-                Input::Left => {
-                    if self.sel == Sel::No {
-                        self.sel = Sel::Yes;
-                    }
+            let (mode, input) = (self.mode(), self.input());
+            let mut keymap = KEYMAP.lock().unwrap();
+            match keymap.get(mode, key) {
+                Some(other @ Input::Back) | Some(other @ Input::Select)
+                    if other != input
+                        && mode == InputMode::Control
+                        && keymap.keys_for(mode, other) == [key] =>
+                {
+                    self.message = format!(
+                        "{} is the last key for {}, which must stay bound in control mode",
+                        keymap::key_name(key),
+                        keymap::input_name(other)
+                    );
+                    drop(keymap);
+                    self.capturing = false;
+                    self.pending = None;
                     self.full_redraw();
-                    None
+                    return Some(Transition::InputMode(InputMode::Control));
                 }
-                Input::Right => {
-                    if self.sel == Sel::Yes {
-                        self.sel = Sel::No;
-                    }
+                Some(other) if other != input && self.pending != Some(key) => {
+                    self.pending = Some(key);
+                    self.message = format!(
+                        "{} is already bound to {}; press it again to rebind it",
+                        keymap::key_name(key),
+                        keymap::input_name(other)
+                    );
+                    drop(keymap);
                     self.full_redraw();
-                    None
+                    return None;
                 }
-                Input::Select => match self.sel {
-                    Sel::Yes => Some(Transition::Pop),
-                    Sel::No => Some(Transition::Push(self.inner_view.take().unwrap())),
-                },
-                _ => None,
+                _ => {}
             }
-        }
 
-        fn restart(&mut self, from_view: Box<dyn FullView>) -> Option<Transition> {
-            self.inner_view = Some(from_view);
+            let previous = keymap.bind(mode, key, input);
+            self.message = match previous {
+                Some(other) if other != input && keymap.keys_for(mode, other).is_empty() => {
+                    format!(
+                        "Bound {}; {} now has no key in {} mode",
+                        keymap::key_name(key),
+                        keymap::input_name(other),
+                        keymap::mode_name(mode)
+                    )
+                }
+                _ => format!("Bound {}", keymap::key_name(key)),
+            };
+            drop(keymap);
+            self.capturing = false;
+            self.pending = None;
             self.full_redraw();
-            None
-        }
-    }
-
-    impl View {
-        pub fn new() -> View {
-            View {
-                sel: Sel::No,
-                inner_view: None,
-            }
+            Some(Transition::InputMode(InputMode::Control))
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,1553 @@
+//! Snapshot tests, drawing views into a headless frame and comparing the text on screen.
+
+use super::{Input, InputMode, UI};
+use crate::blueprint;
+use crate::frame::{Frame, Headless, FRAME};
+use crate::job::{Job, Payload};
+use crate::keymap::{Keymap, Preset, KEYMAP};
+use crate::news::Topic;
+use crate::orbit::Orbit;
+use crate::rocket::Rocket;
+use crate::sats::{CubeSat, CubeSatClass};
+use crate::settings::{GlyphMode, Numerals, Settings, SETTINGS};
+use crate::units::Mass;
+use crate::GAME;
+use std::sync::{Mutex, MutexGuard};
+use termion::event::{Event, MouseButton, MouseEvent};
+
+lazy_static! {
+    /// The game, settings and frame are global, so only one test can use them at a time.
+    static ref GLOBALS: Mutex<()> = Mutex::new(());
+}
+
+/// Runs a `UI` on a headless screen, starting from a new game.
+struct Harness {
+    ui: UI,
+    _globals: MutexGuard<'static, ()>,
+}
+
+impl Harness {
+    fn new() -> Harness {
+        //A failed test poisons the lock, but everything is reset below anyway
+        let globals = GLOBALS.lock().unwrap_or_else(|e| e.into_inner());
+        *SETTINGS.lock().unwrap() = Settings {
+            glyph_mode: GlyphMode::Ascii,
+            ..Settings::default()
+        };
+        *KEYMAP.lock().unwrap() = Keymap::preset(Preset::Default);
+        *FRAME.lock().unwrap() = Frame::new(Box::new(Headless {
+            width: 60,
+            height: 16,
+        }));
+        GAME.reset();
+
+        let mut ui = UI::new();
+        ui.start();
+        ui.present();
+        Harness {
+            ui,
+            _globals: globals,
+        }
+    }
+
+    /// Feeds each input in turn, panicking if the UI exits part way through.
+    fn input(&mut self, inputs: &[Input]) -> &mut Harness {
+        for &input in inputs {
+            assert!(self.ui.update(input), "UI exited on {:?}", input);
+        }
+        self.ui.present();
+        self
+    }
+
+    /// Clicks the cell at `x`, `y`, counting from one.
+    fn click(&mut self, x: u16, y: u16) -> &mut Harness {
+        let event = Event::Mouse(MouseEvent::Press(MouseButton::Left, x, y));
+        assert!(self.ui.input(&event), "UI exited on click at {}, {}", x, y);
+        self.ui.present();
+        self
+    }
+
+    /// Presses the mouse on one cell and lets it go on another.
+    fn drag(&mut self, from: (u16, u16), to: (u16, u16)) -> &mut Harness {
+        self.click(from.0, from.1);
+        let event = Event::Mouse(MouseEvent::Release(to.0, to.1));
+        assert!(self.ui.input(&event), "UI exited on release at {:?}", to);
+        self.ui.present();
+        self
+    }
+
+    fn screen(&self) -> String {
+        FRAME.lock().unwrap().text()
+    }
+
+    fn assert_screen(&self, expected: &str) {
+        let expected = expected.trim_start_matches('\n').trim_end();
+        let screen = self.screen();
+        assert_eq!(
+            screen, expected,
+            "\n--- screen ---\n{}\n--- expected ---\n{}\n",
+            screen, expected
+        );
+    }
+}
+
+/// The inputs for typing `text`.
+fn type_text(text: &str) -> Vec<Input> {
+    text.chars().map(Input::Type).collect()
+}
+
+fn add_job(customer: &str, kg: u64) {
+    let job = Job {
+        customer: GAME.customers.add(customer),
+        payload: Payload::CubeSat(CubeSat {
+            class: CubeSatClass::CubeSat1U,
+            mass: Mass::kg(kg),
+            orbit: Orbit,
+        }),
+    };
+    GAME.available_jobs.lock().unwrap().push(job);
+}
+
+#[test]
+fn basic_tl_view() {
+    let mut h = Harness::new();
+    h.assert_screen(
+        "
+Cuneiforbits
+> Missions
+  Jobs
+  Rockets
+  Tick
+  News
+  Legend
+  Settings
+  Exit",
+    );
+
+    h.input(&[Input::Down, Input::Down, Input::Up]);
+    h.assert_screen(
+        "
+Cuneiforbits
+  Missions
+> Jobs
+  Rockets
+  Tick
+  News
+  Legend
+  Settings
+  Exit",
+    );
+
+    h.input(&[Input::Up, Input::Up]);
+    h.assert_screen(
+        "
+Cuneiforbits
+  Missions
+  Jobs
+  Rockets
+  Tick
+  News
+  Legend
+  Settings
+> Exit",
+    );
+}
+
+#[test]
+fn jobs_view() {
+    let mut h = Harness::new();
+    add_job("SpaceX", 1);
+    add_job("Rocket Lab", 2);
+
+    h.input(&[Input::Down, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Jobs
+> SpaceX                                              v   X
+    1U CubeSat of 1 kg to Orbit
+  Rocket Lab                                          v   X
+    1U CubeSat of 2 kg to Orbit",
+    );
+
+    h.input(&[Input::Down, Input::Right]);
+    h.assert_screen(
+        "
+Cuneiforbits > Jobs
+  SpaceX                                              v   X
+    1U CubeSat of 1 kg to Orbit
+> Rocket Lab                                         [v]  X
+    1U CubeSat of 2 kg to Orbit",
+    );
+
+    h.input(&[Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Jobs
+> SpaceX                                             [v]  X
+    1U CubeSat of 1 kg to Orbit",
+    );
+    let accepted = GAME.accepted_jobs.lock().unwrap();
+    assert_eq!(accepted.len(), 1);
+    assert_eq!(
+        GAME.customers
+            .on(accepted[0].customer, |c| c.name.clone())
+            .unwrap(),
+        "Rocket Lab"
+    );
+    drop(accepted);
+
+    h.input(&[Input::Right, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Jobs
+> SpaceX                                              v  [X]
+    1U CubeSat of 1 kg to Orbit
+
+
+
+              +------------------------------+
+              | Decline the job from SpaceX? |
+              |         YES      [NO]        |
+              +------------------------------+",
+    );
+    assert_eq!(GAME.available_jobs.lock().unwrap().len(), 1);
+
+    h.input(&[Input::Left, Input::Select]);
+    h.assert_screen("Cuneiforbits > Jobs");
+    assert!(GAME.available_jobs.lock().unwrap().is_empty());
+    assert_eq!(GAME.accepted_jobs.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn sexagesimal_numerals() {
+    let mut h = Harness::new();
+    SETTINGS.lock().unwrap().numerals = Numerals::Sexagesimal;
+    add_job("SpaceX", 75);
+
+    h.input(&[Input::Down, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Jobs
+> SpaceX                                              v   X
+    1U CubeSat of 1,15 kg to Orbit",
+    );
+
+    h.input(&[Input::Details]);
+    h.assert_screen(
+        "
+Cuneiforbits > Jobs
+> SpaceX                                              v   X
+    1U CubeSat of 1,15 (75) kg to Orbit",
+    );
+}
+
+#[test]
+fn news_view() {
+    let mut h = Harness::new();
+    GAME.news.post(Topic::Launch, "Saturn reached orbit");
+    GAME.news.post(Topic::Job, "New job from SpaceX");
+
+    h.input(&[Input::Down]);
+    h.assert_screen(
+        "
+Cuneiforbits
+  Missions
+> Jobs
+  Rockets
+  Tick
+  News
+  Legend
+  Settings
+  Exit
+
+
+
+
+
+
+ Job: New job from SpaceX",
+    );
+
+    h.input(&[Input::Down, Input::Down, Input::Down, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > News
+> Job: New job from SpaceX
+  Launch: Saturn reached orbit
+
+
+
+
+
+
+
+
+
+
+
+
+ Job: New job from SpaceX",
+    );
+
+    //Ticking brings in a job, which is news
+    h.input(&[Input::Back, Input::Up, Input::Select]);
+    assert_eq!(GAME.news.len(), 3);
+    let toast = h.screen().lines().last().unwrap().to_string();
+    assert!(toast.starts_with(" Job: New job from "), "{}", toast);
+}
+
+#[test]
+fn rockets_view() {
+    let mut h = Harness::new();
+    h.input(&[Input::Down, Input::Down, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets [+]  sort   compare",
+    );
+
+    GAME.rocket_designs.lock().unwrap().push(Rocket {
+        name: "Alpha".to_string(),
+        components: vec![GAME.known_components.lock().unwrap()[0].clone()],
+    });
+    GAME.rocket_designs.lock().unwrap().push(Rocket {
+        name: "Beta".to_string(),
+        components: Vec::new(),
+    });
+    h.input(&[Input::Down]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+> Alpha: H                                    edit   rename
+
+  Beta:",
+    );
+
+    h.input(&[Input::Down, Input::Right]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+  Alpha: H
+
+> Beta:                                      [edit]  rename",
+    );
+
+    h.input(&[Input::Select]);
+    assert!(h.screen().starts_with("Cuneiforbits > Rockets > Beta\n"));
+
+    h.input(&[
+        Input::Back,
+        Input::Up,
+        Input::Del,
+        Input::Left,
+        Input::Select,
+    ]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+> Beta:                                       edit   rename",
+    );
+}
+
+#[test]
+fn managing_designs() {
+    let mut h = Harness::new();
+    let known = GAME.known_components.lock().unwrap().clone();
+    let part = |name: &str| known.iter().find(|c| c.name == name).unwrap().clone();
+    GAME.rocket_designs.lock().unwrap().push(Rocket {
+        name: "Beta".to_string(),
+        components: vec![part("Baz"), part("Foo")],
+    });
+    GAME.rocket_designs.lock().unwrap().push(Rocket {
+        name: "Alpha".to_string(),
+        components: vec![part("Foo")],
+    });
+
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Duplicate]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+  Beta: ^H
+
+> Beta copy: ^H                               edit   rename
+
+  Alpha: H",
+    );
+
+    //Rename the copy where it is listed, first to a name that is taken
+    h.input(&[Input::Right, Input::Right, Input::Select]);
+    h.input(&[Input::WordBkSpace, Input::WordBkSpace]);
+    h.input(&type_text("alpha"));
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+  Beta: ^H
+
+> alpha                                       edit  [rename]
+  ! Already taken
+  Alpha: H",
+    );
+    assert_eq!(h.ui.input_mode, InputMode::Type);
+    h.input(&[Input::WordBkSpace]);
+    h.input(&type_text("Gamma"));
+    h.input(&[Input::Type('\n')]);
+    assert_eq!(h.ui.input_mode, InputMode::Control);
+
+    //Sort from the button, then from the palette
+    h.input(&[Input::Up, Input::Up, Input::Right, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +  [sort]  compare
+  Beta: ^H
+
+  Gamma: ^H
+                       +Sort by----+
+  Alpha: H             | > Name    |
+                       |   Mass    |
+                       |   Payload |
+                       +-----------+",
+    );
+    h.input(&[Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +  [sort]  compare
+  Alpha: H
+
+  Beta: ^H
+
+  Gamma: ^H",
+    );
+    h.input(&[Input::Palette]);
+    h.input(&type_text("sort by payload"));
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+> Beta: ^H                                    edit   rename
+
+  Gamma: ^H
+
+  Alpha: H",
+    );
+}
+
+#[test]
+fn blueprints() {
+    let mut h = Harness::new();
+    let dir = std::env::temp_dir().join("cuneiforbits-blueprints-test");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let known = GAME.known_components.lock().unwrap().clone();
+    let part = |name: &str| known.iter().find(|c| c.name == name).unwrap().clone();
+    let saturn = Rocket {
+        name: "Saturn V".to_string(),
+        components: ["Quux", "Corge", "Bar", "Foo"]
+            .iter()
+            .map(|&name| part(name))
+            .collect(),
+    };
+    GAME.rocket_designs.lock().unwrap().push(saturn.clone());
+    let path = blueprint::export(&dir, &saturn).unwrap();
+    assert_eq!(path, dir.join("Saturn-V.blueprint"));
+    let text = std::fs::read_to_string(path).unwrap();
+    assert_eq!(
+        text,
+        "name = Saturn V\n\n# Stage 2\nQuux\nCorge\n# Stage 1\nBar\nFoo\n"
+    );
+
+    //Only the blueprint made of known components, with a name not yet taken, comes in
+    GAME.known_components
+        .lock()
+        .unwrap()
+        .retain(|c| c.name != "Grault");
+    let write = |file: &str, text: &str| std::fs::write(dir.join(file), text).unwrap();
+    write("booster.blueprint", "name = Booster\nBar\nGrault\n");
+    write("pod.blueprint", "# Crew only\nname = Pod\nQuux\n");
+    write("typo.blueprint", "name = Typo\n\nQux\n");
+    write("unnamed.blueprint", "Quux\n");
+    let imported = super::rockets_view::import(&dir).unwrap();
+    assert_eq!(
+        imported.lines().collect::<Vec<_>>(),
+        [
+            "Imported 1 of 5 blueprints",
+            "Saturn-V.blueprint, Saturn V is already a design",
+            "booster.blueprint, line 3: Grault has not been unlocked",
+            "typo.blueprint, line 3: there is no component called Qux",
+            "unnamed.blueprint, no name is given",
+        ]
+    );
+    h.input(&[Input::Down, Input::Down, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+> Saturn V: C=TH                              edit   rename
+
+  Pod: C",
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn comparing_designs() {
+    let mut h = Harness::new();
+    let known = GAME.known_components.lock().unwrap().clone();
+    let part = |name: &str| known.iter().find(|c| c.name == name).unwrap().clone();
+    for (name, parts) in [
+        ("Light", vec!["Foo"]),
+        ("Crewed", vec!["Quux", "Corge", "Bar", "Foo"]),
+        ("Cargo", vec!["Baz", "Bar", "Foo"]),
+    ] {
+        GAME.rocket_designs.lock().unwrap().push(Rocket {
+            name: name.to_string(),
+            components: parts.iter().map(|&name| part(name)).collect(),
+        });
+    }
+
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Palette]);
+    h.input(&type_text("compare"));
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > Compare
+              [Light]    Crewed    Cargo
+               H         C=TH      ^TH
+
+Mass           12 kg     38 kg     36 kg
+Delta-v        0 m/s     38 m/s    39 m/s
+  Stage 1      0 m/s     38 m/s    39 m/s
+  Stage 2      -         0 m/s     -
+Fairing volume 0 m³      0 m³      3 m³",
+    );
+    let best = FRAME.lock().unwrap().inverted();
+    assert_eq!(best, ["12 kg", "39 m/s", "39 m/s", "3 m³"]);
+
+    //Without the lightest, the cargo rocket is lightest too
+    h.input(&[Input::Del]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > Compare
+              [Crewed]   Cargo
+               C=TH      ^TH
+
+Mass           38 kg     36 kg
+Delta-v        38 m/s    39 m/s
+  Stage 1      38 m/s    39 m/s
+  Stage 2      0 m/s     -
+Fairing volume 0 m³      3 m³",
+    );
+    let best = FRAME.lock().unwrap().inverted();
+    assert_eq!(best, ["36 kg", "39 m/s", "39 m/s", "3 m³"]);
+
+    h.input(&[Input::Right, Input::Select]);
+    assert!(h
+        .screen()
+        .starts_with("Cuneiforbits > Rockets > Compare > Cargo\n"));
+}
+
+#[test]
+fn rocket_detail_view() {
+    let mut h = Harness::new();
+    let known = GAME.known_components.lock().unwrap().clone();
+    let part = |name: &str| known.iter().find(|c| c.name == name).unwrap().clone();
+    GAME.rocket_designs.lock().unwrap().push(Rocket {
+        name: "Saturn".to_string(),
+        components: [
+            "Quux", "Bar", "Foo", "Corge", "Bar", "Foo", "Grault", "Bar", "Foo",
+        ]
+        .iter()
+        .map(|&name| part(name))
+        .collect(),
+    });
+
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Select]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > Saturn
+Mass: 88 kg
+Stages: 2
+Payload: 0 m³                                       /\
+C Quux (Crewed capsule)                            /  \
+T Bar (Cryogenic tank)                            /    \
+H Foo (Hydrolox engine)                           [ o  ]
+= Corge (Stage decoupler)                         |....|
+T Bar (Cryogenic tank)                            |....|
+H Foo (Hydrolox engine)                           \____/
++ Grault (Booster decoupler)                       /  \
+T Bar (Cryogenic tank)                            ======
+H Foo (Hydrolox engine)                       |..||....||..|
+                                              |..||....||..|
+                                              \__/\____/\__/
+                                               /\  /  \  /\",
+    );
+
+    h.input(&[Input::Select, Input::Down, Input::Select]);
+    assert!(h.screen().contains("Rename to:"));
+    h.input(&[Input::Home, Input::Del, Input::Type('\n')]);
+    let name = GAME.rocket_designs.lock().unwrap()[0].name.clone();
+    assert_eq!(name, "aturn");
+
+    h.input(&[Input::Del]);
+    assert!(h.screen().contains("Delete aturn?"));
+    h.input(&[Input::Left, Input::Select]);
+    let designs = GAME.rocket_designs.lock().unwrap().len();
+    assert_eq!(designs, 0);
+    assert!(h.screen().starts_with("Cuneiforbits > Rockets\n"));
+}
+
+#[test]
+fn validated_prompts() {
+    let mut h = Harness::new();
+    let known = GAME.known_components.lock().unwrap().clone();
+    let part = |name: &str| known.iter().find(|c| c.name == name).unwrap().clone();
+    for name in &["Alpha", "Beta"] {
+        GAME.rocket_designs.lock().unwrap().push(Rocket {
+            name: name.to_string(),
+            components: vec![part("Baz"), part("Bar"), part("Foo")],
+        });
+    }
+    let names = || -> Vec<String> {
+        let designs = GAME.rocket_designs.lock().unwrap();
+        designs.iter().map(|r| r.name.clone()).collect()
+    };
+
+    //Moving takes a whole number
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Select]);
+    h.input(&[
+        Input::Select,
+        Input::Down,
+        Input::Down,
+        Input::Down,
+        Input::Down,
+    ]);
+    h.input(&[Input::Select, Input::BkSpace]);
+    h.input(&type_text("2nd\n"));
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > Alpha
+Mass: 36 kg
+Stages: 1
+Payload: 3 m³
+^ Baz (Small fairing)
+T Bar (Cryogenic+--------------------------+
+H Foo (Hydrolox | Move to position:        |
+                | 2nd                      |
+                | ! Must be a whole number |
+                +--------------------------+            /\
+                                                       /  \
+                                                      /    \
+                                                      |....|
+                                                      |....|
+                                                      \____/
+                                                       /  \",
+    );
+    h.input(&[Input::BkSpace, Input::BkSpace, Input::Type('\n')]);
+    assert_eq!(names(), ["Beta", "Alpha"]);
+    assert!(h.screen().starts_with("Cuneiforbits > Rockets > Alpha\n"));
+
+    //Checking the fit takes a volume, in any unit of volume
+    h.input(&[Input::Select, Input::Up, Input::Select]);
+    h.input(&type_text("3 kg\n"));
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > Alpha
+Mass: 36 kg
+Stages: 1
+Payload: 3 m³
+^ Baz (Small fairing)
+T Bar (Cryog+----------------------------------+
+H Foo (Hydro| Payload volume:                  |
+            | 3 kg                             |
+            | ! Must be a number of m³, ft³, l |
+            +----------------------------------+        /\
+                                                       /  \
+                                                      /    \
+                                                      |....|
+                                                      |....|
+                                                      \____/
+                                                       /  \",
+    );
+    h.input(&[Input::BkSpace, Input::BkSpace]);
+    h.input(&type_text("ft³\n"));
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > Alpha
+Mass: 36 kg
+Stages: 1
+Payload: 3 m³
+^ Baz (Small fairing)
+T Bar (Cryogenic tank)
+H Foo (Hydro+---------------------------------+
+            | It fits, with 2.915 m³ to spare |
+            |               [OK]              |
+            +---------------------------------+         /\
+                                                       /  \
+                                                      /    \
+                                                      |....|
+                                                      |....|
+                                                      \____/
+                                                       /  \",
+    );
+    h.input(&[Input::Select, Input::Select, Input::Up, Input::Select]);
+    h.input(&type_text("12.5m3\n"));
+    assert!(h.screen().contains("It needs "), "{}", h.screen());
+
+    //A blueprint is typed over several lines, and added on Select
+    h.input(&[Input::Select, Input::Back, Input::Palette]);
+    h.input(&type_text("paste blueprint\n"));
+    h.input(&[Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+> Beta: ^TH  +--------------------------------+dit   rename
+             | Blueprint, C-s to add:         |
+  Alpha: ^TH |                                |
+             |                                |
+             |                                |
+             |                                |
+             |                                |
+             |                                |
+             |                                |
+             |                                |
+             | ! Can not be empty             |
+             +--------------------------------+",
+    );
+    h.input(&type_text("name = Gamma\nBaz\nFoo"));
+    h.input(&[Input::Up, Input::End]);
+    h.input(&type_text("\nBar"));
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+> Beta: ^TH  +--------------------------------+dit   rename
+             | Blueprint, C-s to add:         |
+  Alpha: ^TH | name = Gamma                   |
+             | Baz                            |
+             | Bar                            |
+             | Foo                            |
+             |                                |
+             |                                |
+             |                                |
+             |                                |
+             +--------------------------------+",
+    );
+    h.input(&[Input::Select]);
+    assert_eq!(names(), ["Beta", "Alpha", "Gamma"]);
+    let added: Vec<String> = GAME.rocket_designs.lock().unwrap()[2]
+        .components
+        .iter()
+        .map(|c| c.name.clone())
+        .collect();
+    assert_eq!(added, ["Baz", "Bar", "Foo"]);
+
+    h.input(&[Input::Palette]);
+    h.input(&type_text("paste blueprint\nname = Delta\nNope"));
+    h.input(&[Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+  Beta: ^TH
+
+  Alpha: ^TH
+       +-------------------------------------------+
+> Gamma| Can not add it:                           | rename
+       | line 2: there is no component called Nope |
+       |                    [OK]                   |
+       +-------------------------------------------+",
+    );
+    assert_eq!(names().len(), 3);
+}
+
+#[test]
+fn rocket_builder_view() {
+    let mut h = Harness::new();
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+
+
+Components:
+> H Foo (Hydrolox engine)                                  #
+       Mass: 12 kg                                         #
+  T Bar (Cryogenic tank)                                   #
+       Mass: 12 kg                                         #
+  ^ Baz (Small fairing)                                    #
+       Mass: 12 kg                                         #
+  C Quux (Crewed capsule)                                  |
+       Mass: 12 kg                                         |
+                                                           |",
+    );
+
+    h.input(&[Input::Select, Input::Down, Input::Select]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ H T
+
+Components:
+  H Foo (Hydrolox engine)                          #
+       Mass: 12 kg                                 #
+> T Bar (Cryogenic tank)                           #
+       Mass: 12 kg                                 #
+  ^ Baz (Small fairing)                            #
+       Mass: 12 kg                                 #  \____/
+  C Quux (Crewed capsule)                          |   /  \
+       Mass: 12 kg                                 |  |....|
+                                                   |  |....|",
+    );
+
+    h.input(&[Input::Up, Input::Up, Input::Right]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ H T
+   ^
+Components:
+  H Foo (Hydrolox engine)                          #
+       Mass: 12 kg                                 #
+  T Bar (Cryogenic tank)                           #
+       Mass: 12 kg                                 #
+  ^ Baz (Small fairing)                            #
+       Mass: 12 kg                                 #  \____/
+  C Quux (Crewed capsule)                          |   /  \
+       Mass: 12 kg                                 |  |....|
+                                                   |  |....|",
+    );
+
+    h.input(&[Input::Up, Input::Select]);
+    let designs = GAME.rocket_designs.lock().unwrap();
+    assert_eq!(designs.len(), 1);
+    let names: Vec<&str> = designs[0]
+        .components
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, ["Foo", "Bar"]);
+    drop(designs);
+
+    h.input(&[Input::Down, Input::Del, Input::Back]);
+    assert!(h.screen().contains("Unsaved changes"));
+    h.input(&[Input::Down, Input::Select]);
+    assert!(h.screen().starts_with("Cuneiforbits > Rockets\n"));
+    let components = GAME.rocket_designs.lock().unwrap()[0].components.len();
+    assert_eq!(components, 2);
+}
+
+/// Saves the design being built from the palette, returning the names of the components of the
+/// design at `idx`.
+fn save_design(h: &mut Harness, idx: usize) -> Vec<String> {
+    h.input(&[Input::Palette]);
+    h.input(&type_text("save"));
+    h.input(&[Input::Type('\n')]);
+    let designs = GAME.rocket_designs.lock().unwrap();
+    designs[idx]
+        .components
+        .iter()
+        .map(|c| c.name.clone())
+        .collect()
+}
+
+#[test]
+fn rocket_builder_editing() {
+    let mut h = Harness::new();
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Select]);
+    h.input(&[
+        Input::Select,
+        Input::Down,
+        Input::Select,
+        Input::Down,
+        Input::Select,
+    ]);
+
+    //Copy the engine and tank, and paste them after the fairing
+    h.input(&[Input::Up, Input::Up, Input::Up, Input::Mark, Input::Right]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ H T ^
+ ^ ^
+Components:
+  H Foo (Hydrolox engine)                          #
+       Mass: 12 kg                                 #
+  T Bar (Cryogenic tank)                           #  \____/
+       Mass: 12 kg                                 #   /  \
+  ^ Baz (Small fairing)                            #  |....|
+       Mass: 12 kg                                 #  |....|
+  C Quux (Crewed capsule)                          |    /\
+       Mass: 12 kg                                 |   /  \
+                                                   |  /    \",
+    );
+    h.input(&[Input::Copy, Input::Right, Input::Paste]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ H T ^ H T
+       ^ ^                                            \____/
+Components:                                            /  \
+  H Foo (Hydrolox engine)                          #  |....|
+       Mass: 12 kg                                 #  |....|
+  T Bar (Cryogenic tank)                           #    /\
+       Mass: 12 kg                                 #   /  \
+  ^ Baz (Small fairing)                            #  /    \
+       Mass: 12 kg                                 #  \____/
+  C Quux (Crewed capsule)                          |   /  \
+       Mass: 12 kg                                 |  |....|
+                                                   |  |....|",
+    );
+    assert_eq!(save_design(&mut h, 0), ["Foo", "Bar", "Baz", "Foo", "Bar"]);
+
+    //Move the pasted pair in front of the fairing, then duplicate the fairing
+    h.input(&[Input::MoveLeft, Input::Mark, Input::Right, Input::Duplicate]);
+    assert_eq!(
+        save_design(&mut h, 0),
+        ["Foo", "Bar", "Foo", "Bar", "Baz", "Baz"]
+    );
+
+    //Insert a capsule before the second engine
+    h.input(&[Input::Left, Input::Left, Input::Left, Input::InsertBefore]);
+    h.input(&[Input::Down, Input::Down, Input::Down]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+                                                      \____/
+                                                       /  \
+ H T|H T ^ ^                                          |....|
+                                                      |....|
+Components:                                           \____/
+  H Foo (Hydrolox engine)                          #   /  \
+       Mass: 12 kg                                 #  |....|
+  T Bar (Cryogenic tank)                           #  |....|
+       Mass: 12 kg                                 #    /\
+  ^ Baz (Small fairing)                            #   /  \
+       Mass: 12 kg                                 #  /    \
+> C Quux (Crewed capsule)                          |    /\
+       Mass: 12 kg                                 |   /  \
+                                                   |  /    \",
+    );
+    h.input(&[Input::Select]);
+    assert_eq!(
+        save_design(&mut h, 0),
+        ["Foo", "Bar", "Quux", "Foo", "Bar", "Baz", "Baz"]
+    );
+
+    //Cut the capsule and paste it into a new design
+    h.input(&[
+        Input::Home,
+        Input::Up,
+        Input::Right,
+        Input::Right,
+        Input::Cut,
+    ]);
+    assert_eq!(
+        save_design(&mut h, 0),
+        ["Foo", "Bar", "Foo", "Bar", "Baz", "Baz"]
+    );
+    h.input(&[Input::Back, Input::Up, Input::Select, Input::Paste]);
+    assert_eq!(save_design(&mut h, 1), ["Quux"]);
+
+    //Drag the first engine onto the second
+    h.input(&[Input::Back, Input::Up, Input::Left, Input::Select]);
+    h.drag((2, 5), (6, 5));
+    assert_eq!(
+        save_design(&mut h, 0),
+        ["Bar", "Foo", "Foo", "Bar", "Baz", "Baz"]
+    );
+}
+
+#[test]
+fn undoing_edits() {
+    let mut h = Harness::new();
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Select]);
+    h.input(&[
+        Input::Select,
+        Input::Down,
+        Input::Select,
+        Input::Down,
+        Input::Select,
+    ]);
+    assert_eq!(save_design(&mut h, 0), ["Foo", "Bar", "Baz"]);
+
+    //Remove the engine and rename the design, then undo both
+    h.input(&[Input::Up, Input::Up, Input::Up, Input::Del]);
+    h.input(&[
+        Input::Up,
+        Input::Left,
+        Input::WordBkSpace,
+        Input::WordBkSpace,
+    ]);
+    h.input(&type_text("Short"));
+    h.input(&[Input::Type('\n'), Input::Undo]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket            [save]
+
+
+ T ^
+
+Components:
+  H Foo (Hydrolox engine)                          #
+       Mass: 12 kg                                 #
+  T Bar (Cryogenic tank)                           #
+       Mass: 12 kg                                 #
+  ^ Baz (Small fairing)                            #  |....|
+       Mass: 12 kg                                 #  |....|
+  C Quux (Crewed capsule)                          |    /\
+       Mass: 12 kg                                 |   /  \
+                                                   |  /    \",
+    );
+    h.input(&[Input::Undo]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ H T ^
+ ^
+Components:
+  H Foo (Hydrolox engine)                          #
+       Mass: 12 kg                                 #
+  T Bar (Cryogenic tank)                           #  \____/
+       Mass: 12 kg                                 #   /  \
+  ^ Baz (Small fairing)                            #  |....|
+       Mass: 12 kg                                 #  |....|
+  C Quux (Crewed capsule)                          |    /\
+       Mass: 12 kg                                 |   /  \
+                                                   |  /    \",
+    );
+
+    //Redoing takes the engine out again, and reverting puts it back
+    h.input(&[Input::Redo]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ T ^
+ ^
+Components:
+  H Foo (Hydrolox engine)                          #
+       Mass: 12 kg                                 #
+  T Bar (Cryogenic tank)                           #
+       Mass: 12 kg                                 #
+  ^ Baz (Small fairing)                            #  |....|
+       Mass: 12 kg                                 #  |....|
+  C Quux (Crewed capsule)                          |    /\
+       Mass: 12 kg                                 |   /  \
+                                                   |  /    \",
+    );
+    h.input(&[Input::Palette]);
+    h.input(&type_text("revert"));
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ H T ^
+ ^
+Components:
+  H Foo (Hydrolox engine)                          #
+       Mass: 12 kg                                 #
+  T Bar (Cryogenic tank)                           #  \____/
+       Mass: 12 kg                                 #   /  \
+  ^ Baz (Small fairing)                            #  |....|
+       Mass: 12 kg                                 #  |....|
+  C Quux (Crewed capsule)                          |    /\
+       Mass: 12 kg                                 |   /  \
+                                                   |  /    \",
+    );
+
+    //Reverting can be undone too, and leaving asks nothing once the saved design is back
+    h.input(&[Input::Undo]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ T ^
+ ^
+Components:
+  H Foo (Hydrolox engine)                          #
+       Mass: 12 kg                                 #
+  T Bar (Cryogenic tank)                           #
+       Mass: 12 kg                                 #
+  ^ Baz (Small fairing)                            #  |....|
+       Mass: 12 kg                                 #  |....|
+  C Quux (Crewed capsule)                          |    /\
+       Mass: 12 kg                                 |   /  \
+                                                   |  /    \",
+    );
+    h.input(&[Input::Redo, Input::Back]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+> New Rocket: HT^                             edit   rename",
+    );
+}
+
+#[test]
+fn naming_a_design() {
+    let mut h = Harness::new();
+    GAME.rocket_designs.lock().unwrap().push(Rocket {
+        name: "Alpha".to_string(),
+        components: Vec::new(),
+    });
+
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Up]);
+    h.input(&[Input::Select, Input::Up, Input::Up, Input::Left]);
+    h.input(&[Input::WordBkSpace]);
+    h.input(&type_text("Ünïcödé"));
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > New Rocket
+New Ünïcödé            save
+
+
+
+
+Components:
+  H Foo (Hydrolox engine)                                  #
+       Mass: 12 kg                                         #
+  T Bar (Cryogenic tank)                                   #
+       Mass: 12 kg                                         #
+  ^ Baz (Small fairing)                                    #
+       Mass: 12 kg                                         #
+  C Quux (Crewed capsule)                                  |
+       Mass: 12 kg                                         |
+                                                           |",
+    );
+
+    h.input(&[Input::WordBkSpace, Input::WordBkSpace, Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > New Rocket
+                       save
+! Can not be empty
+
+
+
+Components:
+  H Foo (Hydrolox engine)                                  #
+       Mass: 12 kg                                         #
+  T Bar (Cryogenic tank)                                   #
+       Mass: 12 kg                                         #
+  ^ Baz (Small fairing)                                    #
+       Mass: 12 kg                                         #
+  C Quux (Crewed capsule)                                  |
+       Mass: 12 kg                                         |
+                                                           |",
+    );
+    assert_eq!(h.ui.input_mode, InputMode::Type);
+
+    h.input(&type_text("alpha"));
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > New Rocket
+alpha                  save
+! Already taken
+
+
+
+Components:
+  H Foo (Hydrolox engine)                                  #
+       Mass: 12 kg                                         #
+  T Bar (Cryogenic tank)                                   #
+       Mass: 12 kg                                         #
+  ^ Baz (Small fairing)                                    #
+       Mass: 12 kg                                         #
+  C Quux (Crewed capsule)                                  |
+       Mass: 12 kg                                         |
+                                                           |",
+    );
+
+    h.input(&[Input::Home, Input::Del, Input::Type('B'), Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > Blpha
+Blpha                 [save]
+
+
+
+
+Components:
+  H Foo (Hydrolox engine)                                  #
+       Mass: 12 kg                                         #
+  T Bar (Cryogenic tank)                                   #
+       Mass: 12 kg                                         #
+  ^ Baz (Small fairing)                                    #
+       Mass: 12 kg                                         #
+  C Quux (Crewed capsule)                                  |
+       Mass: 12 kg                                         |
+                                                           |",
+    );
+    assert_eq!(h.ui.input_mode, InputMode::Control);
+
+    //The name just given can be recalled when naming again
+    h.input(&[Input::Left, Input::WordBkSpace, Input::Up]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > Blpha
+Blpha                  save
+
+
+
+
+Components:
+  H Foo (Hydrolox engine)                                  #
+       Mass: 12 kg                                         #
+  T Bar (Cryogenic tank)                                   #
+       Mass: 12 kg                                         #
+  ^ Baz (Small fairing)                                    #
+       Mass: 12 kg                                         #
+  C Quux (Crewed capsule)                                  |
+       Mass: 12 kg                                         |
+                                                           |",
+    );
+}
+
+#[test]
+fn breadcrumbs() {
+    let mut h = Harness::new();
+    GAME.rocket_designs.lock().unwrap().push(Rocket {
+        name: "Alpha".to_string(),
+        components: Vec::new(),
+    });
+
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Select]);
+    assert!(h.screen().starts_with("Cuneiforbits > Rockets > Alpha\n"));
+
+    //Clicking "Rockets" pops back to the list, and "Cuneiforbits" to the menu
+    h.click(17, 1);
+    assert!(h.screen().starts_with("Cuneiforbits > Rockets\n"));
+    h.click(2, 1);
+    assert!(h.screen().starts_with("Cuneiforbits\n> Missions"));
+
+    //Saving a new rocket on the way out shows it in place of the builder
+    h.input(&[
+        Input::Down,
+        Input::Down,
+        Input::Select,
+        Input::Up,
+        Input::Select,
+    ]);
+    h.input(&[Input::Down, Input::Select, Input::Back, Input::Select]);
+    assert!(h
+        .screen()
+        .starts_with("Cuneiforbits > Rockets > New Rocket\n"));
+
+    //The builder tells the list which design it saved, which is then selected
+    h.input(&[Input::Back, Input::Select, Input::Select]);
+    h.input(&[Input::Up, Input::Up, Input::Select, Input::Back]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+Rockets  +   sort   compare
+  Alpha:
+
+  New Rocket: T
+
+> New Rocket 2: H                             edit   rename",
+    );
+}
+
+#[test]
+fn leaving_unsaved_changes() {
+    let mut h = Harness::new();
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Select]);
+    h.input(&[Input::Select]);
+
+    //Going elsewhere from the palette asks first, and goes on once the design is saved
+    h.input(&[Input::Palette]);
+    h.input(&type_text("go jobs"));
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        r"
+Cuneiforbits > Rockets > New Rocket
+New Rocket             save
+
+
+ H
+                    +Unsaved changes--+
+Components:         | > Save          |
+> H Foo (Hydrolox en|   Discard       |            #
+       Mass: 12 kg  |   Keep editing  |            #
+  T Bar (Cryogenic t+-----------------+            #
+       Mass: 12 kg                                 #
+  ^ Baz (Small fairing)                            #
+       Mass: 12 kg                                 #
+  C Quux (Crewed capsule)                          |
+       Mass: 12 kg                                 |  \____/
+                                                   |   /  \",
+    );
+    h.input(&[Input::Select]);
+    h.assert_screen("Cuneiforbits > Jobs");
+    assert_eq!(GAME.rocket_designs.lock().unwrap().len(), 1);
+
+    //Clicking a breadcrumb asks too, and discarding drops the changes
+    h.input(&[Input::Palette]);
+    h.input(&type_text("new rocket"));
+    h.input(&[Input::Type('\n'), Input::Select]);
+    h.click(2, 1);
+    assert!(h.screen().contains("Unsaved changes"));
+    h.input(&[Input::Down, Input::Select]);
+    assert!(h.screen().starts_with("Cuneiforbits\n> Missions"));
+    assert_eq!(GAME.rocket_designs.lock().unwrap().len(), 1);
+
+    //Keeping on editing forgets where the player was going, so leaving later goes back
+    h.input(&[Input::Palette]);
+    h.input(&type_text("new rocket"));
+    h.input(&[Input::Type('\n'), Input::Select, Input::Palette]);
+    h.input(&type_text("go jobs"));
+    h.input(&[Input::Type('\n'), Input::Down, Input::Down, Input::Select]);
+    assert!(h.ui.held.is_none());
+    assert!(h
+        .screen()
+        .starts_with("Cuneiforbits > Rockets > New Rocket 2\n"));
+    h.input(&[Input::Back, Input::Down, Input::Select]);
+    assert!(h.screen().starts_with("Cuneiforbits > Rockets\n"));
+}
+
+#[test]
+fn help_overlay() {
+    let mut h = Harness::new();
+    h.input(&[Input::Down, Input::Down, Input::Select, Input::Help]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets
+R+Rockets------------------------------------------------+
+ | > Up, w              Previous                        #|
+ |   Down, s            Next                            #|
+ |   PageUp             Page up                         #|
+ |   PageDown           Page down                       #|
+ |   Home               First                           #|
+ |   End                Last                            #|
+ |   /                  Search                          #|
+ |   Left, a            Move along the design's buttons #|
+ |   Right, d           Move along the design's buttons ||
+ |   Enter, Space       Open                            ||
+ |   Backspace, Delete  Delete the design               ||
+ |   C-d                Copy the design as a new one    ||
+ +-------------------------------------------------------+",
+    );
+
+    h.input(&[Input::Back]);
+    h.assert_screen("Cuneiforbits > Rockets\nRockets [+]  sort   compare");
+
+    //Naming a rocket is typing, so the help lists the typing keys and typing goes on after
+    h.input(&[
+        Input::Select,
+        Input::Up,
+        Input::Up,
+        Input::Left,
+        Input::Help,
+    ]);
+    h.assert_screen(
+        "
+Cuneiforbits > Rockets > New Rocket
+New+New Rocket (typing)--------------------------------+
+   | > C-b, Left    Move the cursor left              #|
+   |   C-f, Right   Move the cursor right             #|
+   |   M-b          Move back a word                  #|
+   |   M-f          Move forward a word               #|
+Com|   C-a, Home    Start of the line                 #|
+  H|   C-e, End     End of the line                   #|   #
+   |   Backspace    Delete before the cursor          #|   #
+  T|   C-d, Delete  Delete after the cursor           #|   #
+   |   C-w          Delete the word before the cursor ||   #
+  ^|   M-d          Delete the word after the cursor  ||   #
+   |   C-p, Up      Previous entry                    ||   #
+  C|   C-n, Down    Next entry                        ||   |
+   +---------------------------------------------------+   |
+                                                           |",
+    );
+    h.input(&[Input::Help]);
+    assert_eq!(h.ui.input_mode, InputMode::Type);
+}
+
+#[test]
+fn command_palette() {
+    let mut h = Harness::new();
+    add_job("SpaceX", 1);
+    add_job("Rocket Lab", 2);
+
+    h.input(&[Input::Palette]);
+    h.assert_screen(
+        "
+Cuneiforbits
+> Missions
+  Jobs     +Command----------------------------+
+  Rockets  | >                                 |
+  Tick     | > go menu                        #|
+  News     |   go missions                    #|
+  Legend   |   go jobs                        #|
+  Settings |   go rockets                     #|
+  Exit     |   go tick                        #|
+           |   go news                        ||
+           |   go legend                      ||
+           |   go settings                    ||
+           +-----------------------------------+",
+    );
+    assert_eq!(h.ui.input_mode, InputMode::Type);
+
+    h.input(&type_text("acc 2"));
+    h.assert_screen(
+        "
+Cuneiforbits
+> Missions
+  Jobs     +Command----------------------------+
+  Rockets  | > acc 2                           |
+  Tick     | > accept job <number>             |
+  News     |                                   |
+  Legend   |                                   |
+  Settings |                                   |
+  Exit     |                                   |
+           |                                   |
+           |                                   |
+           |                                   |
+           +-----------------------------------+",
+    );
+
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits
+> Missions
+  Jobs
+  Rockets
+  Tick
+  News
+  Legend
+  Settings
+  Exit",
+    );
+    let accepted = GAME.accepted_jobs.lock().unwrap()[0].customer;
+    let customer = GAME.customers.on(accepted, |c| c.name.clone()).unwrap();
+    assert_eq!(customer, "Rocket Lab");
+    assert_eq!(h.ui.input_mode, InputMode::Control);
+
+    h.input(&[Input::Palette]);
+    h.input(&type_text("decline job 5"));
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits
+> Missions
+  Jobs
+  Rockets
+  Tick
+  News
+  Legend           +-------------------+
+  Settings         | There is no job 5 |
+  Exit             |        [OK]       |
+                   +-------------------+",
+    );
+    assert_eq!(GAME.available_jobs.lock().unwrap().len(), 1);
+
+    h.input(&[Input::Select, Input::Palette]);
+    h.input(&type_text("go jobs"));
+    h.input(&[Input::Type('\n')]);
+    h.assert_screen(
+        "
+Cuneiforbits > Jobs
+> SpaceX                                              v   X
+    1U CubeSat of 1 kg to Orbit",
+    );
+}
+
+#[test]
+fn legend_view() {
+    let mut h = Harness::new();
+    h.input(&[Input::Up, Input::Up, Input::Up, Input::Select]);
+    h.assert_screen(
+        "
+Cuneiforbits > Legend
+> H Hydrolox engine
+  M Methalox engine
+  K Keralox engine
+  Y Hypergolic engine
+  t Room temperature tank
+  T Cryogenic tank
+  S Deep cryogenic tank
+  ^ Small fairing
+  A Large fairing
+  C Crewed capsule
+  O Uncrewed capsule
+  = Stage decoupler
+  + Booster decoupler",
+    );
+}
+
+#[test]
+fn exit_confirmation_view() {
+    let mut h = Harness::new();
+    h.input(&[Input::Back]);
+    h.assert_screen(
+        "
+Cuneiforbits
+> Missions
+  Jobs
+  Rockets
+  Tick
+  News
+  Legend     +--------------------------------+
+  Settings   | Are you sure you want to exit? |
+  Exit       |          YES      [NO]         |
+             +--------------------------------+",
+    );
+
+    h.input(&[Input::Select]);
+    assert!(h.screen().starts_with("Cuneiforbits"));
+    assert!(!h.screen().contains("exit?"));
+
+    h.input(&[Input::Back, Input::Left]);
+    h.assert_screen(
+        "
+Cuneiforbits
+> Missions
+  Jobs
+  Rockets
+  Tick
+  News
+  Legend     +--------------------------------+
+  Settings   | Are you sure you want to exit? |
+  Exit       |         [YES]      NO          |
+             +--------------------------------+",
+    );
+    assert!(!h.ui.update(Input::Select));
+}
@@ -1,4 +1,4 @@
-use cuneiform_width::cuneiform_width;
+use crate::frame::FRAME;
 
 #[macro_export]
 macro_rules! ui_print {
@@ -10,26 +10,12 @@ macro_rules! ui_print {
     );
 }
 
+/// Draws into the frame, to be shown the next time it is presented.
 pub struct UIStdout;
 
 impl std::fmt::Write for UIStdout {
     fn write_str(&mut self, s: &str) -> std::fmt::Result {
-        use std::io::Write;
-        std::io::stdout()
-            .write_all(proccess(s).as_bytes())
-            .expect("IO error in printing");
+        FRAME.lock().unwrap().write(s);
         Ok(())
     }
 }
-
-fn proccess(s: &str) -> String {
-    let mut out = String::with_capacity(s.len());
-    for c in s.chars() {
-        out.push(c);
-        let width = cuneiform_width(c);
-        if width > 1 {
-            out.push_str(&" ".repeat((width - 1) as usize));
-        }
-    }
-    out
-}